@@ -0,0 +1,102 @@
+//! The audit log, recording every declassification.
+//!
+//! Every call to `Sec::declassify` adds an `AuditRecord` with the levels involved, the policy's reason,
+//! and the place in the source code the call was made from.
+//! Records are kept in memory until they're drained with `take_records`, which a long-running program
+//! should do regularly, e.g. to ship them elsewhere. They can additionally be appended to a file with `log_to_file`.
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::mem;
+use std::panic::Location;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+/// A single declassification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    /// The security level the data was declassified from.
+    pub from: String,
+    /// The security level the data was declassified to.
+    pub to: String,
+    /// The reason given by the policy.
+    pub reason: String,
+    /// Source file of the call to `declassify`.
+    pub file: &'static str,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl AuditRecord {
+    pub(crate) fn new(from: String, to: String, reason: String, location: &'static Location<'static>) -> Self {
+        AuditRecord { from, to, reason, file: location.file(), line: location.line(), column: location.column() }
+    }
+}
+
+impl fmt::Display for AuditRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}: declassified {} -> {}: {}", self.file, self.line, self.column, self.from, self.to, self.reason)
+    }
+}
+
+struct AuditLog {
+    records: Vec<AuditRecord>,
+    file: Option<File>,
+}
+
+static LOG: Mutex<AuditLog> = Mutex::new(AuditLog { records: Vec::new(), file: None });
+
+// A panic while holding the lock can't leave the log in a broken state, so poisoning is ignored
+fn lock() -> MutexGuard<'static, AuditLog> {
+    LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub(crate) fn record(record: AuditRecord) {
+    let mut log = lock();
+
+    if let Some(ref mut file) = log.file {
+        // an unrecorded declassification is worse than a crashed program
+        writeln!(file, "{}", record).expect("failed to write to the audit log file");
+    }
+
+    log.records.push(record);
+}
+
+/// Returns every declassification recorded so far, oldest first.
+pub fn records() -> Vec<AuditRecord> {
+    lock().records.clone()
+}
+
+/// Like `records`, but removes the records from memory, so they don't pile up.
+/// Anything declassified afterwards is recorded anew.
+///
+/// # Example
+/// ```
+/// use seclib::prelude::*;
+/// use seclib::audit;
+/// use seclib::declassify::Policy;
+///
+/// let high = Authority::acquire().unwrap().clearance::<High>();
+/// let length = Policy::new("password length", |p: &str| p.len());
+///
+/// let _: Sec<Low, usize> = Sec::<High, _>::new("hunter2").declassify(&length, &high);
+///
+/// let records = audit::take_records();
+/// assert_eq!(records.len(), 1);
+/// assert_eq!(records[0].reason, "password length");
+/// assert!(audit::records().is_empty());
+/// ```
+pub fn take_records() -> Vec<AuditRecord> {
+    mem::take(&mut lock().records)
+}
+
+/// Starts appending every subsequent declassification to the file at `path`, one line each.
+/// Replaces any file set up by an earlier call.
+///
+/// Failing to write a record to the file panics, rather than let a declassification go unrecorded.
+pub fn log_to_file(path: impl AsRef<Path>) -> io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    lock().file = Some(file);
+    Ok(())
+}
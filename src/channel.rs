@@ -0,0 +1,350 @@
+//! Labeled channels, for passing `Sec`s between threads without unwrapping them.
+//!
+//! A channel carries data of a single security level `S`. Its senders accept any `Sec` of a level &leq; `S`,
+//! and its receiver hands out `Sec<S, A>`s, so whatever goes through stays at least as secret as it was.
+//!
+//! `sec_channel` is unbounded, `sync_sec_channel` has a bounded buffer whose senders block when it's full.
+//! Both wrap `std::sync::mpsc`, and report the same errors; a message that can't be sent is handed back as a `Sec<S, A>`.
+//! `SecSelect` waits on several receivers at once.
+//!
+//! Whether and when a message arrives is not labeled, just like with the channels they wrap.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::mpsc::{self, RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::Sec;
+use super::redact::short_type_name;
+use super::security_level as sl;
+
+/// Creates an unbounded channel for data of security level `S`.
+///
+/// # Examples
+/// ```
+/// use std::thread;
+/// use seclib::prelude::*;
+/// use seclib::channel::sec_channel;
+///
+/// let high = Authority::acquire().unwrap().clearance::<High>();
+///
+/// let (sender, receiver) = sec_channel::<High, u32>();
+/// thread::spawn(move || {
+///     sender.send(Sec::<Low, u32>::new(20)).unwrap();
+///     sender.send(Sec::<High, u32>::new(22)).unwrap();
+/// });
+///
+/// let received: Sec<High, Vec<u32>> = receiver.iter().collect();
+/// assert_eq!(received.map(|v| v.iter().sum::<u32>()).reveal(&high), 42);
+/// ```
+/// Secrets can't be sent over a public channel:
+/// ```compile_fail
+/// use seclib::prelude::*;
+/// use seclib::channel::sec_channel;
+///
+/// let (sender, receiver) = sec_channel::<Low, u32>();
+/// sender.send(Sec::<High, u32>::new(42)); // ERROR: expected `Sec<Low, u32>`, found `Sec<High, u32>`
+/// ```
+pub fn sec_channel<S, A>() -> (SecSender<S, A>, SecReceiver<S, A>)
+where
+    S: sl::SecurityLevel,
+{
+    let (sender, receiver) = mpsc::channel();
+    (SecSender { security_level: PhantomData, sender }, SecReceiver { security_level: PhantomData, receiver })
+}
+
+/// Creates a channel for data of security level `S`, buffering at most `bound` messages.
+///
+/// With a `bound` of 0, every send waits for a matching receive.
+///
+/// # Example
+/// ```
+/// use std::sync::mpsc::TrySendError;
+/// use seclib::prelude::*;
+/// use seclib::channel::sync_sec_channel;
+///
+/// let (sender, receiver) = sync_sec_channel::<High, &str>(1);
+/// sender.try_send(Sec::<High, &str>::new("first")).unwrap();
+///
+/// // the message that didn't fit comes back, still labeled
+/// match sender.try_send(Sec::<Low, &str>::new("second")) {
+///     Err(TrySendError::Full(data)) => assert_eq!(format!("{:?}", data), "Sec<High>(<redacted>)"),
+///     _ => panic!("expected the channel to be full"),
+/// }
+/// assert!(receiver.try_recv().is_ok());
+/// ```
+pub fn sync_sec_channel<S, A>(bound: usize) -> (SyncSecSender<S, A>, SecReceiver<S, A>)
+where
+    S: sl::SecurityLevel,
+{
+    let (sender, receiver) = mpsc::sync_channel(bound);
+    (SyncSecSender { security_level: PhantomData, sender }, SecReceiver { security_level: PhantomData, receiver })
+}
+
+/// The sending half of a `sec_channel`. It can be cloned to send from several threads.
+pub struct SecSender<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    security_level: PhantomData<S>,
+    sender: mpsc::Sender<A>,
+}
+
+impl<S, A> SecSender<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    /// Sends the data of a `Sec` of a level &leq; `S`, without blocking.
+    ///
+    /// Fails when the receiver is gone, giving the data back at the channel's level.
+    pub fn send<S2>(&self, data: Sec<S2, A>) -> Result<(), SendError<Sec<S, A>>>
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+    {
+        self.sender.send(data.data).map_err(|SendError(data)| SendError(Sec::new(data)))
+    }
+}
+
+impl<S, A> Clone for SecSender<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn clone(&self) -> Self {
+        SecSender { security_level: PhantomData, sender: self.sender.clone() }
+    }
+}
+
+impl<S, A> fmt::Debug for SecSender<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecSender<{}>(..)", short_type_name::<S>())
+    }
+}
+
+/// The sending half of a `sync_sec_channel`. It can be cloned to send from several threads.
+pub struct SyncSecSender<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    security_level: PhantomData<S>,
+    sender: mpsc::SyncSender<A>,
+}
+
+impl<S, A> SyncSecSender<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    /// Sends the data of a `Sec` of a level &leq; `S`, blocking while the buffer is full.
+    ///
+    /// Fails when the receiver is gone, giving the data back at the channel's level.
+    pub fn send<S2>(&self, data: Sec<S2, A>) -> Result<(), SendError<Sec<S, A>>>
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+    {
+        self.sender.send(data.data).map_err(|SendError(data)| SendError(Sec::new(data)))
+    }
+
+    /// Sends the data of a `Sec` of a level &leq; `S`, failing instead of blocking when the buffer is full.
+    pub fn try_send<S2>(&self, data: Sec<S2, A>) -> Result<(), TrySendError<Sec<S, A>>>
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+    {
+        self.sender.try_send(data.data).map_err(|error| match error {
+            TrySendError::Full(data) => TrySendError::Full(Sec::new(data)),
+            TrySendError::Disconnected(data) => TrySendError::Disconnected(Sec::new(data)),
+        })
+    }
+}
+
+impl<S, A> Clone for SyncSecSender<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn clone(&self) -> Self {
+        SyncSecSender { security_level: PhantomData, sender: self.sender.clone() }
+    }
+}
+
+impl<S, A> fmt::Debug for SyncSecSender<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SyncSecSender<{}>(..)", short_type_name::<S>())
+    }
+}
+
+/// The receiving half of a `sec_channel` or `sync_sec_channel`.
+pub struct SecReceiver<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    security_level: PhantomData<S>,
+    receiver: mpsc::Receiver<A>,
+}
+
+impl<S, A> SecReceiver<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    /// Waits for the next message. Fails once every sender is gone and the channel is empty.
+    pub fn recv(&self) -> Result<Sec<S, A>, RecvError> {
+        self.receiver.recv().map(Sec::new)
+    }
+
+    /// Returns the next message if there is one, without waiting.
+    pub fn try_recv(&self) -> Result<Sec<S, A>, TryRecvError> {
+        self.receiver.try_recv().map(Sec::new)
+    }
+
+    /// Waits at most `timeout` for the next message.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Sec<S, A>, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout).map(Sec::new)
+    }
+
+    /// Iterates over the messages, waiting for each one, until every sender is gone.
+    pub fn iter(&self) -> impl Iterator<Item = Sec<S, A>> + '_ {
+        self.receiver.iter().map(Sec::new)
+    }
+}
+
+impl<S, A> fmt::Debug for SecReceiver<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecReceiver<{}>(..)", short_type_name::<S>())
+    }
+}
+
+// How long `SecSelect` sleeps between polls, at most
+const MAX_BACKOFF: Duration = Duration::from_millis(1);
+
+/// Waits on several receivers at once, of any levels &leq; `S`, handing out messages at `S`.
+///
+/// Receivers are polled in the order they were added, backing off between rounds while they're all empty.
+///
+/// # Example
+/// ```
+/// use std::thread;
+/// use seclib::prelude::*;
+/// use seclib::channel::{sec_channel, SecSelect};
+///
+/// let high = Authority::acquire().unwrap().clearance::<High>();
+///
+/// let (public, public_receiver) = sec_channel::<Low, &str>();
+/// let (secret, secret_receiver) = sec_channel::<High, &str>();
+///
+/// let mut select = SecSelect::<High, &str>::new();
+/// let from_public = select.add(&public_receiver);
+/// let from_secret = select.add(&secret_receiver);
+///
+/// thread::spawn(move || secret.send(Sec::<High, &str>::new("secret")).unwrap());
+///
+/// let (index, message) = select.recv().unwrap();
+/// assert_eq!(index, from_secret);
+/// assert_eq!(message.reveal(&high), "secret");
+///
+/// drop(public);
+/// assert!(select.recv().is_err());
+/// ```
+pub struct SecSelect<'a, S, A>
+where
+    S: sl::SecurityLevel,
+{
+    security_level: PhantomData<S>,
+    receivers: Vec<Box<dyn Fn() -> Result<A, TryRecvError> + 'a>>,
+}
+
+impl<'a, S, A> SecSelect<'a, S, A>
+where
+    S: sl::SecurityLevel,
+    A: 'a,
+{
+    /// Constructor. Starts out without any receivers.
+    pub fn new() -> Self {
+        SecSelect { security_level: PhantomData, receivers: Vec::new() }
+    }
+
+    /// Adds a receiver of a level &leq; `S`, returning the index its messages will be reported with.
+    pub fn add<S2>(&mut self, receiver: &'a SecReceiver<S2, A>) -> usize
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+    {
+        self.receivers.push(Box::new(move || receiver.receiver.try_recv()));
+        self.receivers.len() - 1
+    }
+
+    /// Returns the first message found, along with the index of its receiver, without waiting.
+    ///
+    /// Fails with `Disconnected` once every receiver is disconnected, or when there are none.
+    pub fn try_recv(&self) -> Result<(usize, Sec<S, A>), TryRecvError> {
+        let mut connected = false;
+        for (index, receiver) in self.receivers.iter().enumerate() {
+            match receiver() {
+                Ok(data) => return Ok((index, Sec::new(data))),
+                Err(TryRecvError::Empty) => connected = true,
+                Err(TryRecvError::Disconnected) => {}
+            }
+        }
+        Err(if connected { TryRecvError::Empty } else { TryRecvError::Disconnected })
+    }
+
+    /// Waits for the first message on any of the receivers.
+    pub fn recv(&self) -> Result<(usize, Sec<S, A>), RecvError> {
+        self.poll(None).map_err(|_| RecvError)
+    }
+
+    /// Waits at most `timeout` for the first message on any of the receivers.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<(usize, Sec<S, A>), RecvTimeoutError> {
+        self.poll(Some(Instant::now() + timeout))
+    }
+
+    fn poll(&self, deadline: Option<Instant>) -> Result<(usize, Sec<S, A>), RecvTimeoutError> {
+        let mut backoff = Duration::from_micros(1);
+        loop {
+            match self.try_recv() {
+                Ok(message) => return Ok(message),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            let mut pause = backoff;
+            if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+                pause = pause.min(deadline - now);
+            }
+            thread::sleep(pause);
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+}
+
+impl<'a, S, A> Default for SecSelect<'a, S, A>
+where
+    S: sl::SecurityLevel,
+    A: 'a,
+{
+    fn default() -> Self {
+        SecSelect::new()
+    }
+}
+
+impl<'a, S, A> fmt::Debug for SecSelect<'a, S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecSelect<{}>({} receivers)", short_type_name::<S>(), self.receivers.len())
+    }
+}
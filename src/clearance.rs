@@ -0,0 +1,171 @@
+//! Clearances, the capabilities needed to look inside a `Sec`.
+//!
+//! Security levels are plain unit structs, so anyone can write `High`. Revealing data takes a `Clearance<High>` instead,
+//! which can't be made out of thin air: the only source of clearances is the process' `Authority`,
+//! which can be acquired exactly once, typically at the top of `main`.
+//! From there, clearances are handed to the code that needs them, as arguments, like any other capability.
+//!
+//! A clearance can be lowered, e.g. a `Clearance<High>` gives a `Clearance<Low>`, but never raised.
+//! `DynClearance` is the runtime counterpart, needed to reveal a `DynSec`,
+//! and a `Privilege` is the capability to act as a principal, see the `principal` module.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::dynamic::{FlowError, Label, StaticLabel};
+use super::principal::{Principal, Privilege};
+use super::redact::short_type_name;
+use super::security_level as sl;
+
+static ACQUIRED: AtomicBool = AtomicBool::new(false);
+
+/// The one source of clearances in a process.
+#[derive(Debug)]
+pub struct Authority {
+    _private: (),
+}
+
+impl Authority {
+    /// Returns the authority the first time it's called, and `None` ever after.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// assert!(Authority::acquire().is_none());
+    ///
+    /// let data: Sec<High, &str> = Sec::new("Attack at Dawn!");
+    /// assert_eq!(data.reveal(&authority.clearance::<High>()), "Attack at Dawn!");
+    /// ```
+    pub fn acquire() -> Option<Authority> {
+        if ACQUIRED.swap(true, Ordering::SeqCst) {
+            None
+        } else {
+            Some(Authority { _private: () })
+        }
+    }
+
+    /// Mints a clearance for the security level `S`.
+    pub fn clearance<S>(&self) -> Clearance<S>
+    where
+        S: sl::SecurityLevel,
+    {
+        Clearance::mint()
+    }
+
+    /// Mints a clearance for the runtime label `label`.
+    pub fn dyn_clearance<L>(&self, label: L) -> DynClearance<L>
+    where
+        L: Label,
+    {
+        DynClearance { label }
+    }
+
+    /// Mints the privilege to act as `principal`.
+    pub fn privilege(&self, principal: &Principal) -> Privilege {
+        Privilege::mint(principal.clone())
+    }
+}
+
+/// The capability to look at data of security level `S`, or anything below it.
+///
+/// Clearances can't be cloned, so handing one out is always explicit: lend it, or `lower` it first.
+pub struct Clearance<S>
+where
+    S: sl::SecurityLevel,
+{
+    security_level: PhantomData<S>,
+}
+
+impl<S> Clearance<S>
+where
+    S: sl::SecurityLevel,
+{
+    pub(crate) const fn mint() -> Self {
+        Clearance { security_level: PhantomData }
+    }
+
+    /// Returns a clearance for a level &leq; `S`.
+    ///
+    /// # Examples
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let high = authority.clearance::<High>();
+    ///
+    /// let data: Sec<Low, i32> = Sec::new(42);
+    /// assert_eq!(data.reveal(&high.lower::<Low>()), 42);
+    /// ```
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let low = authority.clearance::<Low>();
+    ///
+    /// let high = low.lower::<High>(); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// ```
+    pub fn lower<S2>(&self) -> Clearance<S2>
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+    {
+        Clearance::mint()
+    }
+
+    /// Returns the runtime counterpart of this clearance.
+    pub fn to_dynamic<L>(&self) -> DynClearance<L>
+    where
+        S: StaticLabel<L>,
+        L: Label,
+    {
+        DynClearance { label: S::label() }
+    }
+}
+
+impl<S> fmt::Debug for Clearance<S>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Clearance<{}>", short_type_name::<S>())
+    }
+}
+
+/// The capability to look at data labeled `label`, or anything that can flow to it.
+pub struct DynClearance<L>
+where
+    L: Label,
+{
+    pub(crate) label: L,
+}
+
+impl<L> DynClearance<L>
+where
+    L: Label,
+{
+    /// The label this clearance is for.
+    pub fn label(&self) -> &L {
+        &self.label
+    }
+
+    /// Returns a clearance for `label`, provided it can flow to this clearance's label.
+    pub fn lower(&self, label: L) -> Result<DynClearance<L>, FlowError<L>> {
+        if label.can_flow_to(&self.label) {
+            Ok(DynClearance { label })
+        } else {
+            Err(FlowError { from: label, to: self.label.clone() })
+        }
+    }
+}
+
+impl<L> fmt::Debug for DynClearance<L>
+where
+    L: Label,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DynClearance<{:?}>", self.label)
+    }
+}
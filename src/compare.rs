@@ -0,0 +1,219 @@
+//! Comparing and hashing `Sec` values.
+//!
+//! `Sec` doesn't implement `PartialEq` and friends, as every comparison would hand out a bit of the secret for free.
+//! Instead there are two ways of comparing:
+//!
+//! * The `labeled_*` methods, which are always available, but whose result stays within a `Sec` of the same level.
+//! * `Cleared`, which bundles a `Sec` with proof of clearance (same as `reveal`),
+//!   and implements `Eq`, `Ord`, and `Hash` so labeled values can be used as keys in maps and sets.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use super::Sec;
+use super::clearance::Clearance;
+use super::redact::short_type_name;
+use super::security_level as sl;
+
+impl<S, A> Sec<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    /// Compares two `Sec`s for equality, keeping the answer at the same security level.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let password: Sec<High, &str> = Sec::new("hunter2");
+    /// let guess: Sec<High, &str> = Sec::new("password1");
+    ///
+    /// let result: Sec<High, bool> = password.labeled_eq(&guess);
+    ///
+    /// assert_eq!(result.reveal(&high), false);
+    /// ```
+    pub fn labeled_eq(&self, other: &Sec<S, A>) -> Sec<S, bool>
+    where
+        A: PartialEq,
+    {
+        Sec::new(self.data == other.data)
+    }
+
+    /// The labeled counterpart to `PartialOrd::partial_cmp`.
+    pub fn labeled_partial_cmp(&self, other: &Sec<S, A>) -> Sec<S, Option<Ordering>>
+    where
+        A: PartialOrd,
+    {
+        Sec::new(self.data.partial_cmp(&other.data))
+    }
+
+    /// The labeled counterpart to `Ord::cmp`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let salary: Sec<High, u32> = Sec::new(52_000);
+    /// let bonus: Sec<High, u32> = Sec::new(4_000);
+    ///
+    /// assert_eq!(salary.labeled_cmp(&bonus).reveal(&high), Ordering::Greater);
+    /// ```
+    pub fn labeled_cmp(&self, other: &Sec<S, A>) -> Sec<S, Ordering>
+    where
+        A: Ord,
+    {
+        Sec::new(self.data.cmp(&other.data))
+    }
+
+    /// Hashes the data with a fresh `H`, keeping the resulting hash at the same security level.
+    pub fn labeled_hash<H>(&self) -> Sec<S, u64>
+    where
+        A: Hash,
+        H: Hasher + Default,
+    {
+        let mut hasher = H::default();
+        self.data.hash(&mut hasher);
+        Sec::new(hasher.finish())
+    }
+
+    /// Bundles the `Sec` with proof of clearance, making it comparable and hashable.
+    /// Like `reveal`, it must be supplied with a `Clearance` for a security level &geq; the `Sec`'s.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::BTreeSet;
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let high = authority.clearance::<High>();
+    ///
+    /// let mut codes = BTreeSet::new();
+    /// codes.insert(Sec::<High, u32>::new(1234).cleared(&high));
+    /// codes.insert(Sec::<High, u32>::new(1234).cleared(&high));
+    ///
+    /// assert_eq!(codes.len(), 1);
+    /// ```
+    /// Without sufficient clearance there's nothing to compare:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let code: Sec<High, u32> = Sec::new(1234);
+    /// let key = code.cleared(&authority.clearance::<Low>()); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// ```
+    pub fn cleared<C>(self, _: &Clearance<C>) -> Cleared<C, S, A>
+    where
+        C: sl::SecurityLevel<S> + sl::SecurityLevel,
+    {
+        Cleared { clearance: PhantomData, sec: self }
+    }
+}
+
+/// A `Sec` paired with a clearance `C` &geq; its level `S`.
+///
+/// Since clearance was proven when it was created, it can implement `Eq`, `Ord`, and `Hash` by looking at the data.
+/// It still formats as redacted, and the data can only be taken back out as a `Sec`.
+pub struct Cleared<C, S, A>
+where
+    C: sl::SecurityLevel<S> + sl::SecurityLevel,
+    S: sl::SecurityLevel,
+{
+    clearance: PhantomData<C>,
+    sec: Sec<S, A>,
+}
+
+impl<C, S, A> Cleared<C, S, A>
+where
+    C: sl::SecurityLevel<S> + sl::SecurityLevel,
+    S: sl::SecurityLevel,
+{
+    /// Borrows the underlying `Sec`.
+    pub fn as_sec(&self) -> &Sec<S, A> {
+        &self.sec
+    }
+
+    /// Gives back the underlying `Sec`, dropping the clearance.
+    pub fn into_sec(self) -> Sec<S, A> {
+        self.sec
+    }
+}
+
+impl<C, S, A> Clone for Cleared<C, S, A>
+where
+    C: sl::SecurityLevel<S> + sl::SecurityLevel,
+    S: sl::SecurityLevel,
+    A: Clone,
+{
+    fn clone(&self) -> Self {
+        Cleared { clearance: PhantomData, sec: Sec::new(self.sec.data.clone()) }
+    }
+}
+
+impl<C, S, A> fmt::Debug for Cleared<C, S, A>
+where
+    C: sl::SecurityLevel<S> + sl::SecurityLevel,
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cleared<{}>({:?})", short_type_name::<C>(), self.sec)
+    }
+}
+
+impl<C, S, A> PartialEq for Cleared<C, S, A>
+where
+    C: sl::SecurityLevel<S> + sl::SecurityLevel,
+    S: sl::SecurityLevel,
+    A: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.sec.data == other.sec.data
+    }
+}
+
+impl<C, S, A> Eq for Cleared<C, S, A>
+where
+    C: sl::SecurityLevel<S> + sl::SecurityLevel,
+    S: sl::SecurityLevel,
+    A: Eq,
+{
+}
+
+impl<C, S, A> PartialOrd for Cleared<C, S, A>
+where
+    C: sl::SecurityLevel<S> + sl::SecurityLevel,
+    S: sl::SecurityLevel,
+    A: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.sec.data.partial_cmp(&other.sec.data)
+    }
+}
+
+impl<C, S, A> Ord for Cleared<C, S, A>
+where
+    C: sl::SecurityLevel<S> + sl::SecurityLevel,
+    S: sl::SecurityLevel,
+    A: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sec.data.cmp(&other.sec.data)
+    }
+}
+
+impl<C, S, A> Hash for Cleared<C, S, A>
+where
+    C: sl::SecurityLevel<S> + sl::SecurityLevel,
+    S: sl::SecurityLevel,
+    A: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sec.data.hash(state)
+    }
+}
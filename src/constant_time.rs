@@ -0,0 +1,287 @@
+//! Constant-time comparisons and selection of labeled data.
+//!
+//! `labeled_eq` keeps its answer secret, but not how long it took to get it: comparing byte strings
+//! stops at the first difference, so timing a MAC check tells an attacker how much of their guess was right.
+//! The operations here look at every byte (or bit) whatever the data, and answer with a `Choice` instead of a `bool`,
+//! so not even the compiler gets a branch to hang off the secret.
+//!
+//! Lengths are considered public: slices of different lengths compare unequal right away,
+//! and selecting between vectors of different lengths panics.
+
+use std::hint;
+use std::ops::{BitAnd, BitOr, Not};
+
+use super::Sec;
+use super::security_level as sl;
+
+/// A secret boolean, represented as 0 or 1, to be combined without branching on it.
+#[derive(Debug, Clone, Copy)]
+pub struct Choice(u8);
+
+impl Choice {
+    // `value` must be 0 or 1. The value goes through an optimization barrier,
+    // so the compiler can't reason its way back to a `bool` and branch on it.
+    fn new(value: u8) -> Self {
+        Choice(hint::black_box(value))
+    }
+
+    /// The choice as 0 or 1.
+    pub fn unwrap_u8(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<bool> for Choice {
+    fn from(value: bool) -> Self {
+        Choice::new(value as u8)
+    }
+}
+
+impl From<Choice> for bool {
+    fn from(choice: Choice) -> Self {
+        choice.0 == 1
+    }
+}
+
+impl BitAnd for Choice {
+    type Output = Choice;
+
+    fn bitand(self, other: Choice) -> Choice {
+        Choice::new(self.0 & other.0)
+    }
+}
+
+impl BitOr for Choice {
+    type Output = Choice;
+
+    fn bitor(self, other: Choice) -> Choice {
+        Choice::new(self.0 | other.0)
+    }
+}
+
+impl Not for Choice {
+    type Output = Choice;
+
+    fn not(self) -> Choice {
+        Choice::new(self.0 ^ 1)
+    }
+}
+
+/// Equality in time independent of the values compared.
+pub trait ConstantTimeEq {
+    /// Whether `self` and `other` are equal.
+    fn ct_eq(&self, other: &Self) -> Choice;
+}
+
+/// Ordering in time independent of the values compared.
+pub trait ConstantTimeLess: ConstantTimeEq {
+    /// Whether `self` is less than `other`.
+    fn ct_lt(&self, other: &Self) -> Choice;
+}
+
+/// Selection in time independent of the choice.
+pub trait ConditionallySelectable: Sized {
+    /// Returns `a` when `choice` is 0, and `b` when it's 1.
+    fn ct_select(a: &Self, b: &Self, choice: Choice) -> Self;
+}
+
+macro_rules! constant_time_int {
+    ($($t:ty as $u:ty),*) => {
+        $(
+            impl ConstantTimeEq for $t {
+                fn ct_eq(&self, other: &Self) -> Choice {
+                    let x = (*self ^ *other) as $u;
+                    // the top bit of `x | -x` is set exactly when `x` isn't 0
+                    let nonzero = (x | x.wrapping_neg()) >> (<$u>::BITS - 1);
+                    Choice::new(nonzero as u8 ^ 1)
+                }
+            }
+
+            impl ConstantTimeLess for $t {
+                fn ct_lt(&self, other: &Self) -> Choice {
+                    // flipping the sign bit orders signed values like unsigned ones, and is a no-op for unsigned ones
+                    let flip = <$t>::MIN as $u;
+                    let (a, b) = ((*self as $u) ^ flip, (*other as $u) ^ flip);
+                    // the borrow out of `a - b`, computed without comparing
+                    let borrow = (!a & b) | (!(a ^ b) & a.wrapping_sub(b));
+                    Choice::new((borrow >> (<$u>::BITS - 1)) as u8)
+                }
+            }
+
+            impl ConditionallySelectable for $t {
+                fn ct_select(a: &Self, b: &Self, choice: Choice) -> Self {
+                    let mask = (0 as $u).wrapping_sub(choice.0 as $u) as $t;
+                    *a ^ (mask & (*a ^ *b))
+                }
+            }
+        )*
+    };
+}
+
+constant_time_int!(
+    u8 as u8, u16 as u16, u32 as u32, u64 as u64, u128 as u128, usize as usize,
+    i8 as u8, i16 as u16, i32 as u32, i64 as u64, i128 as u128, isize as usize
+);
+
+impl<T> ConstantTimeEq for [T]
+where
+    T: ConstantTimeEq,
+{
+    fn ct_eq(&self, other: &Self) -> Choice {
+        if self.len() != other.len() {
+            return Choice::new(0);
+        }
+        self.iter().zip(other).fold(Choice::new(1), |equal, (a, b)| equal & a.ct_eq(b))
+    }
+}
+
+impl<T> ConstantTimeLess for [T]
+where
+    T: ConstantTimeLess,
+{
+    /// Compares lexicographically. Only the common prefix is compared in constant time,
+    /// if that's equal the shorter slice is less.
+    fn ct_lt(&self, other: &Self) -> Choice {
+        let (less, equal) = self.iter().zip(other).fold((Choice::new(0), Choice::new(1)), |(less, equal), (a, b)| {
+            (less | (equal & a.ct_lt(b)), equal & a.ct_eq(b))
+        });
+        less | (equal & Choice::from(self.len() < other.len()))
+    }
+}
+
+impl<T> ConstantTimeEq for Vec<T>
+where
+    T: ConstantTimeEq,
+{
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self[..].ct_eq(&other[..])
+    }
+}
+
+impl<T> ConstantTimeLess for Vec<T>
+where
+    T: ConstantTimeLess,
+{
+    fn ct_lt(&self, other: &Self) -> Choice {
+        self[..].ct_lt(&other[..])
+    }
+}
+
+impl<T> ConditionallySelectable for Vec<T>
+where
+    T: ConditionallySelectable,
+{
+    /// # Panics
+    /// When `a` and `b` differ in length.
+    fn ct_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        assert_eq!(a.len(), b.len(), "can only select between vectors of the same length");
+        a.iter().zip(b).map(|(a, b)| T::ct_select(a, b, choice)).collect()
+    }
+}
+
+impl<T, const N: usize> ConstantTimeEq for [T; N]
+where
+    T: ConstantTimeEq,
+{
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self[..].ct_eq(&other[..])
+    }
+}
+
+impl<T, const N: usize> ConstantTimeLess for [T; N]
+where
+    T: ConstantTimeLess,
+{
+    fn ct_lt(&self, other: &Self) -> Choice {
+        self[..].ct_lt(&other[..])
+    }
+}
+
+impl<T, const N: usize> ConditionallySelectable for [T; N]
+where
+    T: ConditionallySelectable + Copy,
+{
+    fn ct_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut result = *a;
+        for (r, b) in result.iter_mut().zip(b) {
+            *r = T::ct_select(r, b, choice);
+        }
+        result
+    }
+}
+
+impl<T> ConstantTimeEq for &T
+where
+    T: ConstantTimeEq + ?Sized,
+{
+    fn ct_eq(&self, other: &Self) -> Choice {
+        (**self).ct_eq(*other)
+    }
+}
+
+impl<T> ConstantTimeLess for &T
+where
+    T: ConstantTimeLess + ?Sized,
+{
+    fn ct_lt(&self, other: &Self) -> Choice {
+        (**self).ct_lt(*other)
+    }
+}
+
+impl<S, A> Sec<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    /// Compares two `Sec`s for equality in constant time, keeping the answer at the same security level.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let expected: Sec<High, Vec<u8>> = Sec::new(vec![0xde, 0xad, 0xbe, 0xef]);
+    /// let received: Sec<High, Vec<u8>> = Sec::new(vec![0xde, 0xad, 0xbe, 0xef]);
+    ///
+    /// let valid: Sec<High, bool> = expected.ct_eq(&received).map(bool::from);
+    /// assert_eq!(valid.reveal(&high), true);
+    /// ```
+    pub fn ct_eq(&self, other: &Sec<S, A>) -> Sec<S, Choice>
+    where
+        A: ConstantTimeEq,
+    {
+        Sec::new(self.data.ct_eq(&other.data))
+    }
+
+    /// Whether this `Sec` is less than `other`, in constant time, keeping the answer at the same security level.
+    pub fn ct_lt(&self, other: &Sec<S, A>) -> Sec<S, Choice>
+    where
+        A: ConstantTimeLess,
+    {
+        Sec::new(self.data.ct_lt(&other.data))
+    }
+
+    /// Picks this `Sec`'s data when `choice` is 0, and `other`'s when it's 1, in time independent of the choice.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let balance: Sec<High, u64> = Sec::new(100);
+    /// let price: Sec<High, u64> = Sec::new(30);
+    ///
+    /// // pay if affordable, without branching on either amount
+    /// let affordable = balance.ct_lt(&price).map(|c| !c);
+    /// let remaining = balance.ct_select(&balance.clone().map(|b| b.wrapping_sub(30)), &affordable);
+    ///
+    /// assert_eq!(remaining.reveal(&high), 70);
+    /// ```
+    pub fn ct_select(&self, other: &Sec<S, A>, choice: &Sec<S, Choice>) -> Sec<S, A>
+    where
+        A: ConditionallySelectable,
+    {
+        Sec::new(A::ct_select(&self.data, &other.data, choice.data))
+    }
+}
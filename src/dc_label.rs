@@ -0,0 +1,239 @@
+//! Runtime labels in the style of DC-labels (disjunction category labels), as used by Haskell's LIO.
+//!
+//! A `DCLabel` has a secrecy and an integrity `Component`, each a boolean formula over principals
+//! in conjunctive normal form, like `(alice \/ bob) /\ carol`.
+//! The secrecy component says whose privileges it takes to read the data, the integrity component who vouches for it.
+//!
+//! A label `L1` can flow to `L2` when `L2`'s secrecy implies `L1`'s (it's at least as secret),
+//! and `L1`'s integrity implies `L2`'s (it's at least as trustworthy).
+//!
+//! `DCLabel` implements `Label`, so `DCSec` is just a `DynSec` and has the same `map`, `and_then`, and `zip_with`.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use super::dynamic::{DynSec, Label};
+use super::principal::Principal;
+
+// A disjunction of principals
+type Clause = BTreeSet<Principal>;
+
+#[derive(Clone, PartialEq)]
+enum Formula {
+    False,
+    // a conjunction of clauses, where the empty conjunction is true
+    Cnf(BTreeSet<Clause>),
+}
+
+/// A boolean formula over principals, in conjunctive normal form.
+///
+/// Formulas are kept normalized: no clause is a superset of another, as it would be implied by it anyway,
+/// and a conjunction containing an empty clause is `False`. That makes structurally equal formulas logically equal too.
+///
+/// # Example
+/// ```
+/// use seclib::principal::Principal;
+/// use seclib::dc_label::Component;
+///
+/// let alice = Component::principal(&Principal::new("alice"));
+/// let bob = Component::principal(&Principal::new("bob"));
+/// let carol = Component::principal(&Principal::new("carol"));
+///
+/// let formula = alice.or(&bob.and(&carol));
+///
+/// assert_eq!(format!("{:?}", formula), "(alice \\/ bob) /\\ (alice \\/ carol)");
+/// assert!(bob.and(&carol).implies(&formula));
+/// assert!(!bob.implies(&formula));
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct Component {
+    formula: Formula,
+}
+
+impl Component {
+    /// The formula that's always true, i.e. the empty conjunction.
+    pub fn always_true() -> Self {
+        Component { formula: Formula::Cnf(BTreeSet::new()) }
+    }
+
+    /// The formula that's always false.
+    pub fn always_false() -> Self {
+        Component { formula: Formula::False }
+    }
+
+    /// The formula consisting of a single principal.
+    pub fn principal(principal: &Principal) -> Self {
+        Self::from_clauses(vec![vec![principal.clone()]])
+    }
+
+    /// The conjunction of the given disjunctions of principals.
+    pub fn from_clauses<C, P>(clauses: C) -> Self
+    where
+        C: IntoIterator<Item = P>,
+        P: IntoIterator<Item = Principal>,
+    {
+        Self::normalize(clauses.into_iter().map(|clause| clause.into_iter().collect()).collect())
+    }
+
+    fn normalize(clauses: BTreeSet<Clause>) -> Self {
+        if clauses.iter().any(BTreeSet::is_empty) {
+            return Self::always_false();
+        }
+
+        // drop every clause subsumed by a smaller one
+        let kept = clauses.iter()
+            .filter(|clause| !clauses.iter().any(|other| other != *clause && other.is_subset(clause)))
+            .cloned()
+            .collect();
+
+        Component { formula: Formula::Cnf(kept) }
+    }
+
+    /// Whether this is the formula that's always true.
+    pub fn is_true(&self) -> bool {
+        match self.formula {
+            Formula::Cnf(ref clauses) => clauses.is_empty(),
+            Formula::False => false,
+        }
+    }
+
+    /// Whether this is the formula that's always false.
+    pub fn is_false(&self) -> bool {
+        self.formula == Formula::False
+    }
+
+    /// The conjunction `self /\ other`.
+    pub fn and(&self, other: &Self) -> Self {
+        match (&self.formula, &other.formula) {
+            (Formula::Cnf(a), Formula::Cnf(b)) => Self::normalize(a.union(b).cloned().collect()),
+            _ => Self::always_false(),
+        }
+    }
+
+    /// The disjunction `self \/ other`, distributed back into conjunctive normal form.
+    pub fn or(&self, other: &Self) -> Self {
+        match (&self.formula, &other.formula) {
+            (Formula::False, _) => other.clone(),
+            (_, Formula::False) => self.clone(),
+            (Formula::Cnf(a), Formula::Cnf(b)) => {
+                if a.is_empty() || b.is_empty() {
+                    return Self::always_true();
+                }
+                let clauses = a.iter()
+                    .flat_map(|x| b.iter().map(move |y| x.union(y).cloned().collect()))
+                    .collect();
+                Self::normalize(clauses)
+            }
+        }
+    }
+
+    /// Whether `self` logically implies `other`.
+    ///
+    /// As neither formula has negations, that's the case exactly when every clause of `other`
+    /// contains some clause of `self`.
+    pub fn implies(&self, other: &Self) -> bool {
+        match (&self.formula, &other.formula) {
+            (Formula::False, _) => true,
+            (_, Formula::False) => false,
+            (Formula::Cnf(a), Formula::Cnf(b)) => b.iter().all(|y| a.iter().any(|x| x.is_subset(y))),
+        }
+    }
+}
+
+impl fmt::Debug for Component {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let clauses = match self.formula {
+            Formula::False => return write!(f, "False"),
+            Formula::Cnf(ref clauses) if clauses.is_empty() => return write!(f, "True"),
+            Formula::Cnf(ref clauses) => clauses,
+        };
+
+        let multiple = clauses.len() > 1;
+        for (i, clause) in clauses.iter().enumerate() {
+            if i > 0 {
+                write!(f, " /\\ ")?;
+            }
+            let names: Vec<&str> = clause.iter().map(Principal::name).collect();
+            if multiple && names.len() > 1 {
+                write!(f, "({})", names.join(" \\/ "))?;
+            } else {
+                write!(f, "{}", names.join(" \\/ "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A DC-label, with a secrecy and an integrity component.
+#[derive(Clone, PartialEq)]
+pub struct DCLabel {
+    pub secrecy: Component,
+    pub integrity: Component,
+}
+
+/// Data labeled with a `DCLabel`.
+pub type DCSec<A> = DynSec<A, DCLabel>;
+
+impl DCLabel {
+    /// Constructor.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::principal::Principal;
+    /// use seclib::dc_label::{Component, DCLabel, DCSec};
+    ///
+    /// let alice = Component::principal(&Principal::new("alice"));
+    /// let bob = Component::principal(&Principal::new("bob"));
+    /// let carol = Component::principal(&Principal::new("carol"));
+    ///
+    /// // readable by alice, or by bob and carol together
+    /// let label = DCLabel::new(alice.or(&bob.and(&carol)), Component::always_true());
+    /// let data: DCSec<&str> = DCSec::new(label, "the plan");
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let bob_only = authority.dyn_clearance(DCLabel::new(bob.clone(), Component::always_true()));
+    /// let bob_and_carol = authority.dyn_clearance(DCLabel::new(bob.and(&carol), Component::always_true()));
+    ///
+    /// assert!(data.clone().reveal(&bob_only).is_err());
+    /// assert_eq!(data.reveal(&bob_and_carol), Ok("the plan"));
+    /// ```
+    pub fn new(secrecy: Component, integrity: Component) -> Self {
+        DCLabel { secrecy, integrity }
+    }
+
+    /// The label readable by anyone, and vouched for by no one in particular: `<True, True>`.
+    pub fn public() -> Self {
+        DCLabel::new(Component::always_true(), Component::always_true())
+    }
+
+    /// The least restrictive label, which can flow anywhere: `<True, False>`.
+    pub fn bottom() -> Self {
+        DCLabel::new(Component::always_true(), Component::always_false())
+    }
+
+    /// The most restrictive label, which nothing else can flow to: `<False, True>`.
+    pub fn top() -> Self {
+        DCLabel::new(Component::always_false(), Component::always_true())
+    }
+}
+
+impl Label for DCLabel {
+    fn can_flow_to(&self, other: &Self) -> bool {
+        other.secrecy.implies(&self.secrecy) && self.integrity.implies(&other.integrity)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        DCLabel::new(self.secrecy.and(&other.secrecy), self.integrity.or(&other.integrity))
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        DCLabel::new(self.secrecy.or(&other.secrecy), self.integrity.and(&other.integrity))
+    }
+}
+
+impl fmt::Debug for DCLabel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{:?}, {:?}>", self.secrecy, self.integrity)
+    }
+}
@@ -0,0 +1,129 @@
+//! Policy-controlled declassification.
+//!
+//! `reveal` takes data out of a `Sec` for good, without saying why. Declassification instead moves data down to a
+//! lower security level, but only through an explicit `Declassifier`, and never without leaving a trace in the `audit` log.
+//!
+//! Anyone can write a policy, so what authorizes a declassification is a `Clearance` for the level the data comes from:
+//! only code that could `reveal` the data anyway may release it.
+
+use std::marker::PhantomData;
+use std::panic::Location;
+
+use super::Sec;
+use super::audit::{self, AuditRecord};
+use super::clearance::Clearance;
+use super::redact::short_type_name;
+use super::security_level as sl;
+
+/// A policy for moving data from the security level `From` down to `To`.
+///
+/// The policy decides what actually gets through, e.g. only an aggregate or a hash of the data.
+pub trait Declassifier<From, To>
+where
+    From: sl::SecurityLevel,
+    To: sl::SecurityLevel,
+{
+    type Input;
+    type Output;
+
+    /// Why this policy is allowed to declassify, as recorded in the audit log.
+    fn reason(&self) -> String;
+
+    /// Turns the data at level `From` into what is released at level `To`.
+    fn declassify(&self, data: Self::Input) -> Self::Output;
+}
+
+/// A `Declassifier` made from a reason and a function.
+pub struct Policy<From, To, A, B> {
+    levels: PhantomData<(From, To)>,
+    reason: String,
+    f: Box<dyn Fn(A) -> B>,
+}
+
+impl<From, To, A, B> Policy<From, To, A, B>
+where
+    From: sl::SecurityLevel,
+    To: sl::SecurityLevel,
+{
+    /// Constructor. `f` decides what gets released, and `reason` is what ends up in the audit log.
+    pub fn new<F>(reason: impl Into<String>, f: F) -> Self
+    where
+        F: Fn(A) -> B + 'static,
+    {
+        Policy { levels: PhantomData, reason: reason.into(), f: Box::new(f) }
+    }
+}
+
+impl<From, To, A, B> Declassifier<From, To> for Policy<From, To, A, B>
+where
+    From: sl::SecurityLevel,
+    To: sl::SecurityLevel,
+{
+    type Input = A;
+    type Output = B;
+
+    fn reason(&self) -> String {
+        self.reason.clone()
+    }
+
+    fn declassify(&self, data: A) -> B {
+        (self.f)(data)
+    }
+}
+
+impl<S, A> Sec<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    /// Moves the data to the security level `S2` by passing it through `policy`.
+    /// Like `reveal`, it must be supplied with a `Clearance` for a security level &geq; the `Sec`'s.
+    /// The call is recorded in the `audit` log, along with the policy's reason and where it was made.
+    ///
+    /// # Examples
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::audit;
+    /// use seclib::declassify::Policy;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    ///
+    /// let salaries: Sec<High, Vec<u32>> = Sec::new(vec![52_000, 31_000, 40_000]);
+    /// let average = Policy::new("publish the average salary", |s: Vec<u32>| s.iter().sum::<u32>() / s.len() as u32);
+    ///
+    /// let result: Sec<Low, u32> = salaries.declassify(&average, &authority.clearance::<High>());
+    ///
+    /// assert_eq!(result.reveal(&authority.clearance::<Low>()), 41_000);
+    ///
+    /// let record = audit::records().pop().unwrap();
+    /// assert_eq!((record.from.as_str(), record.to.as_str()), ("High", "Low"));
+    /// assert_eq!(record.reason, "publish the average salary");
+    /// ```
+    /// Holding a policy isn't enough, declassifying takes a clearance for the data:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    /// use seclib::declassify::Policy;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    ///
+    /// let password: Sec<High, &str> = Sec::new("hunter2");
+    /// let everything = Policy::new("no reason", |p: &str| p);
+    ///
+    /// let result: Sec<Low, &str> = password.declassify(&everything, &authority.clearance::<Low>()); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// ```
+    #[track_caller]
+    pub fn declassify<S2, S3, P>(self, policy: &P, _: &Clearance<S3>) -> Sec<S2, P::Output>
+    where
+        S2: sl::SecurityLevel,
+        S3: sl::SecurityLevel<S> + sl::SecurityLevel,
+        P: Declassifier<S, S2, Input = A>,
+    {
+        audit::record(AuditRecord::new(
+            short_type_name::<S>(),
+            short_type_name::<S2>(),
+            policy.reason(),
+            Location::caller(),
+        ));
+
+        Sec::new(policy.declassify(self.data))
+    }
+}
@@ -0,0 +1,234 @@
+//! Runtime labels in the style of Myers and Liskov's Decentralized Label Model (DLM).
+//!
+//! A `DlmLabel` is a set of policies, each with an *owner* and the *readers* the owner allows.
+//! Data may only be read by principals every owner allows, and every owner controls their own policy only:
+//! they can `declassify` by adding readers to it, but can't touch anybody else's.
+//!
+//! `DlmLabel` implements `Label`, so `DlmSec` is just a `DynSec` and has the same `map`, `and_then`, and `zip_with`.
+//! The `Label` impl orders labels structurally, which is always safe. Methods taking a `PrincipalHierarchy`
+//! also take acts-for relations into account, which lets more flows through.
+//! Reading and declassifying as a principal take their `Privilege`, so nobody can claim to be `alice` just by naming her.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::panic::Location;
+
+use super::audit::{self, AuditRecord};
+use super::dynamic::{DynSec, FlowError, Label};
+use super::principal::{Principal, PrincipalHierarchy, Privilege};
+
+/// A DLM label, e.g. `{alice: bob, carol; dave: }`, where `alice` allows `bob` and `carol` to read, and `dave` allows no one.
+/// Owners can always read, so only `alice` and `dave` together could read that data.
+#[derive(Clone, PartialEq, Default)]
+pub struct DlmLabel {
+    // one policy per owner, as two policies of the same owner are equivalent to the intersection of their readers
+    policies: BTreeMap<Principal, BTreeSet<Principal>>,
+}
+
+/// Data labeled with a `DlmLabel`.
+pub type DlmSec<A> = DynSec<A, DlmLabel>;
+
+impl DlmLabel {
+    /// Constructor. The label without any policies, readable by anyone.
+    pub fn public() -> Self {
+        Self::default()
+    }
+
+    /// Adds a policy of `owner`, allowing only `readers` to read.
+    /// If `owner` already has a policy, only readers allowed by both are kept.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::principal::Principal;
+    /// use seclib::dlm::DlmLabel;
+    ///
+    /// let alice = Principal::new("alice");
+    /// let bob = Principal::new("bob");
+    ///
+    /// let label = DlmLabel::public().with_policy(&alice, vec![bob]);
+    ///
+    /// assert_eq!(format!("{:?}", label), "{alice: bob}");
+    /// ```
+    pub fn with_policy(mut self, owner: &Principal, readers: impl IntoIterator<Item = Principal>) -> Self {
+        let readers: BTreeSet<Principal> = readers.into_iter().collect();
+        let policy = match self.policies.remove(owner) {
+            Some(existing) => existing.intersection(&readers).cloned().collect(),
+            None => readers,
+        };
+        self.policies.insert(owner.clone(), policy);
+        self
+    }
+
+    /// The owners of the label's policies.
+    pub fn owners(&self) -> impl Iterator<Item = &Principal> {
+        self.policies.keys()
+    }
+
+    /// The readers `owner` allows, if `owner` has a policy in the label.
+    pub fn readers(&self, owner: &Principal) -> Option<&BTreeSet<Principal>> {
+        self.policies.get(owner)
+    }
+
+    /// Whether `reader` may read data with this label, given the acts-for relations in `hierarchy`.
+    pub fn can_read(&self, reader: &Principal, hierarchy: &PrincipalHierarchy) -> bool {
+        self.policies.iter().all(|(owner, readers)| {
+            hierarchy.acts_for(reader, owner) || readers.iter().any(|r| hierarchy.acts_for(reader, r))
+        })
+    }
+
+    /// Whether data labeled `self` may flow to `other`, given the acts-for relations in `hierarchy`.
+    ///
+    /// That's the case when every policy in `self` is enforced by some policy in `other`,
+    /// whose owner acts for the original owner, and whose readers could all read under the original policy.
+    pub fn can_flow_to_in(&self, other: &Self, hierarchy: &PrincipalHierarchy) -> bool {
+        self.policies.iter().all(|(owner, readers)| {
+            other.policies.iter().any(|(other_owner, other_readers)| {
+                hierarchy.acts_for(other_owner, owner)
+                    && other_readers.iter().all(|reader| {
+                        hierarchy.acts_for(reader, owner) || readers.iter().any(|r| hierarchy.acts_for(reader, r))
+                    })
+            })
+        })
+    }
+}
+
+impl Label for DlmLabel {
+    fn can_flow_to(&self, other: &Self) -> bool {
+        self.can_flow_to_in(other, &PrincipalHierarchy::new())
+    }
+
+    /// Both labels' policies, so only principals allowed by both can read.
+    fn join(&self, other: &Self) -> Self {
+        other.policies.iter().fold(self.clone(), |label, (owner, readers)| label.with_policy(owner, readers.iter().cloned()))
+    }
+
+    /// The policies of owners in both labels, allowing anyone either one allows.
+    fn meet(&self, other: &Self) -> Self {
+        let policies = self.policies.iter()
+            .filter_map(|(owner, readers)| {
+                other.policies.get(owner).map(|other_readers| (owner.clone(), readers.union(other_readers).cloned().collect()))
+            })
+            .collect();
+        DlmLabel { policies }
+    }
+}
+
+impl fmt::Debug for DlmLabel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{")?;
+        for (i, (owner, readers)) in self.policies.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            let readers: Vec<&str> = readers.iter().map(Principal::name).collect();
+            write!(f, "{}: {}", owner, readers.join(", "))?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Reasons a DLM operation can fail.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DlmError {
+    /// Only `owner`, or a principal acting for them, may change their policy.
+    NotOwner { principal: Principal, owner: Principal },
+    /// `reader` isn't allowed to read data labeled `label`.
+    CannotRead { reader: Principal, label: DlmLabel },
+}
+
+impl fmt::Display for DlmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DlmError::NotOwner { ref principal, ref owner } => {
+                write!(f, "{} doesn't act for {}, and can't change their policy", principal, owner)
+            }
+            DlmError::CannotRead { ref reader, ref label } => write!(f, "{} can't read data labeled {:?}", reader, label),
+        }
+    }
+}
+
+impl Error for DlmError {}
+
+impl<A> DynSec<A, DlmLabel> {
+    /// Returns the data, provided the principal `reader` is the privilege for may read it.
+    pub fn reveal_to(self, reader: &Privilege, hierarchy: &PrincipalHierarchy) -> Result<A, DlmError> {
+        let reader = reader.principal();
+        if self.label.can_read(reader, hierarchy) {
+            Ok(self.data)
+        } else {
+            Err(DlmError::CannotRead { reader: reader.clone(), label: self.label })
+        }
+    }
+
+    /// Relabels the data with `label`, given the acts-for relations in `hierarchy`.
+    pub fn raise_in(self, label: DlmLabel, hierarchy: &PrincipalHierarchy) -> Result<Self, FlowError<DlmLabel>> {
+        if self.label.can_flow_to_in(&label, hierarchy) {
+            Ok(DynSec::new(label, self.data))
+        } else {
+            Err(FlowError { from: self.label, to: label })
+        }
+    }
+
+    /// Replaces `owner`'s policy with one allowing `readers`, on behalf of the principal `principal` is the privilege for,
+    /// who must act for `owner`.
+    /// Every other policy stays in place, so the data doesn't become readable by anyone the other owners don't allow.
+    ///
+    /// Like `Sec::declassify`, the call is recorded in the `audit` log.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::principal::{Principal, PrincipalHierarchy};
+    /// use seclib::dlm::{DlmLabel, DlmSec};
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let alice = authority.privilege(&Principal::new("alice"));
+    /// let bob = authority.privilege(&Principal::new("bob"));
+    /// let hierarchy = PrincipalHierarchy::new();
+    ///
+    /// let data: DlmSec<&str> = DlmSec::new(DlmLabel::public().with_policy(alice.principal(), vec![]), "alice's diary");
+    /// assert!(data.clone().reveal_to(&bob, &hierarchy).is_err());
+    ///
+    /// assert!(data.clone().declassify(&bob, &hierarchy, alice.principal(), vec![bob.principal().clone()]).is_err());
+    ///
+    /// let shared = data.declassify(&alice, &hierarchy, alice.principal(), vec![bob.principal().clone()]).unwrap();
+    /// assert_eq!(shared.reveal_to(&bob, &hierarchy), Ok("alice's diary"));
+    /// ```
+    /// Naming a principal isn't enough to act as them:
+    /// ```compile_fail
+    /// use seclib::principal::{Principal, PrincipalHierarchy};
+    /// use seclib::dlm::{DlmLabel, DlmSec};
+    ///
+    /// let alice = Principal::new("alice");
+    /// let data: DlmSec<&str> = DlmSec::new(DlmLabel::public().with_policy(&alice, vec![]), "alice's diary");
+    ///
+    /// let diary = data.reveal_to(&alice, &PrincipalHierarchy::new()); // ERROR: expected `&Privilege`, found `&Principal`
+    /// ```
+    #[track_caller]
+    pub fn declassify(
+        self,
+        principal: &Privilege,
+        hierarchy: &PrincipalHierarchy,
+        owner: &Principal,
+        readers: impl IntoIterator<Item = Principal>,
+    ) -> Result<Self, DlmError> {
+        let principal = principal.principal();
+        if !hierarchy.acts_for(principal, owner) {
+            return Err(DlmError::NotOwner { principal: principal.clone(), owner: owner.clone() });
+        }
+
+        let DynSec { label, data } = self;
+        let mut declassified = label.clone();
+        declassified.policies.insert(owner.clone(), readers.into_iter().collect());
+
+        audit::record(AuditRecord::new(
+            format!("{:?}", label),
+            format!("{:?}", declassified),
+            format!("declassified by {}", principal),
+            Location::caller(),
+        ));
+
+        Ok(DynSec::new(declassified, data))
+    }
+}
@@ -0,0 +1,277 @@
+//! Runtime (dynamic) labels.
+//!
+//! The levels in `security_level` are types, so they have to be known at compile time.
+//! When the sensitivity of data is only known at runtime, e.g. when it comes from a database row or depends on a user's role,
+//! `DynSec` carries the label around as a value instead, and checks flows when they happen.
+//!
+//! Static levels with a runtime counterpart implement `StaticLabel`, which makes it possible to convert between
+//! `Sec` and `DynSec`. Going from `Sec` to `DynSec` always works; the other way around is checked.
+//!
+//! Revealing a `DynSec` takes a `DynClearance`, which like a `Clearance` has to come from the `Authority`.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use super::Sec;
+use super::clearance::DynClearance;
+use super::security_level as sl;
+
+/// A runtime label. Labels are ordered by `can_flow_to`, and form a lattice with `join` and `meet`.
+pub trait Label: Clone + fmt::Debug + PartialEq {
+    /// Whether data labeled `self` may end up somewhere labeled `other`, i.e. `self` &leq; `other`.
+    fn can_flow_to(&self, other: &Self) -> bool;
+
+    /// The least upper bound of the two labels.
+    fn join(&self, other: &Self) -> Self;
+
+    /// The greatest lower bound of the two labels.
+    fn meet(&self, other: &Self) -> Self;
+}
+
+/// The runtime counterpart to `Low` and `High`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Level {
+    Low,
+    High,
+}
+
+impl Label for Level {
+    fn can_flow_to(&self, other: &Self) -> bool {
+        self <= other
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        *self.max(other)
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        *self.min(other)
+    }
+}
+
+/// A static security level with a runtime counterpart of type `L`.
+pub trait StaticLabel<L>: sl::SecurityLevel + Sized
+where
+    L: Label,
+{
+    fn label() -> L;
+}
+
+impl StaticLabel<Level> for sl::Low {
+    fn label() -> Level {
+        Level::Low
+    }
+}
+
+impl StaticLabel<Level> for sl::High {
+    fn label() -> Level {
+        Level::High
+    }
+}
+
+/// Data may not flow from one label to another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowError<L> {
+    pub from: L,
+    pub to: L,
+}
+
+impl<L> fmt::Display for FlowError<L>
+where
+    L: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "data labeled {:?} can't flow to {:?}", self.from, self.to)
+    }
+}
+
+impl<L> Error for FlowError<L> where L: fmt::Debug {}
+
+/// The dynamic counterpart to `Sec`, which wraps data with a runtime label.
+///
+/// Like `Sec`, it never formats its data, and it can only be taken out with sufficient clearance.
+#[derive(Clone)]
+pub struct DynSec<A, L = Level>
+where
+    L: Label,
+{
+    pub(crate) label: L,
+    pub(crate) data: A,
+}
+
+impl<A, L> DynSec<A, L>
+where
+    L: Label,
+{
+    /// Constructor. Labels `data` with `label`.
+    pub fn new(label: L, data: A) -> Self {
+        DynSec { label, data }
+    }
+
+    /// The label of the data.
+    pub fn label(&self) -> &L {
+        &self.label
+    }
+
+    /// Maps a function over a `DynSec` and returns a new `DynSec` with the same label.
+    pub fn map<B, F>(self, f: F) -> DynSec<B, L>
+    where
+        F: FnOnce(A) -> B,
+    {
+        let DynSec { label, data } = self;
+        DynSec { label, data: f(data) }
+    }
+
+    /// Flat maps a function over a `DynSec`.
+    ///
+    /// The result is labeled with the join of this `DynSec`'s label and `label`, which are both known before `f` runs,
+    /// so the label that comes back says nothing about the data. Should `f` return data with a label that can't flow there,
+    /// the result is a `FlowError` instead, labeled just the same, like with LIO's `toLabeled`.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::dynamic::Level;
+    ///
+    /// let high = Authority::acquire().unwrap().dyn_clearance(Level::High);
+    ///
+    /// let data = DynSec::new(Level::Low, 4);
+    /// let result = data.clone().and_then(Level::High, |i| DynSec::new(Level::High, i + 2));
+    ///
+    /// assert_eq!(result.label(), &Level::High);
+    /// assert_eq!(result.reveal(&high), Ok(Ok(6)));
+    ///
+    /// // too high a label for the result is an error, but only seen with clearance
+    /// let result = data.and_then(Level::Low, |i| DynSec::new(Level::High, i + 2));
+    ///
+    /// assert_eq!(result.label(), &Level::Low);
+    /// assert!(result.reveal(&high).unwrap().is_err());
+    /// ```
+    pub fn and_then<B, F>(self, label: L, f: F) -> DynSec<Result<B, FlowError<L>>, L>
+    where
+        F: FnOnce(A) -> DynSec<B, L>,
+    {
+        let DynSec { label: own, data } = self;
+        let label = own.join(&label);
+        let result = f(data);
+
+        let data = if result.label.can_flow_to(&label) {
+            Ok(result.data)
+        } else {
+            Err(FlowError { from: result.label, to: label.clone() })
+        };
+        DynSec { label, data }
+    }
+
+    /// Combines two `DynSec`s with a function, resulting in a new `DynSec` labeled with the join of both labels.
+    pub fn zip_with<B, C, F>(self, other: DynSec<B, L>, f: F) -> DynSec<C, L>
+    where
+        F: FnOnce(A, B) -> C,
+    {
+        DynSec { label: self.label.join(&other.label), data: f(self.data, other.data) }
+    }
+
+    /// Relabels the data with a label at least as high as the current one.
+    pub fn raise(self, label: L) -> Result<Self, FlowError<L>> {
+        if self.label.can_flow_to(&label) {
+            Ok(DynSec { label, data: self.data })
+        } else {
+            Err(FlowError { from: self.label, to: label })
+        }
+    }
+
+    /// Returns the data, provided `clearance` is at least as high as its label.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::dynamic::Level;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let data = DynSec::new(Level::High, "Attack at Dawn!");
+    ///
+    /// assert!(data.clone().reveal(&authority.dyn_clearance(Level::Low)).is_err());
+    /// assert_eq!(data.reveal(&authority.clearance::<High>().to_dynamic()), Ok("Attack at Dawn!"));
+    /// ```
+    pub fn reveal(self, clearance: &DynClearance<L>) -> Result<A, FlowError<L>> {
+        if self.label.can_flow_to(&clearance.label) {
+            Ok(self.data)
+        } else {
+            Err(FlowError { from: self.label, to: clearance.label.clone() })
+        }
+    }
+
+    /// Converts a `Sec` into a `DynSec`, labeled with the runtime counterpart of its level.
+    pub fn from_sec<S>(sec: Sec<S, A>) -> Self
+    where
+        S: StaticLabel<L>,
+    {
+        DynSec { label: S::label(), data: sec.data }
+    }
+
+    /// Converts a `DynSec` into a `Sec<S, A>`, provided its label can flow to `S`.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::dynamic::Level;
+    ///
+    /// let row = DynSec::new(Level::Low, "public");
+    /// let data: Sec<High, &str> = row.into_sec().unwrap();
+    ///
+    /// let row = DynSec::new(Level::High, "secret");
+    /// assert!(row.into_sec::<Low>().is_err());
+    /// ```
+    pub fn into_sec<S>(self) -> Result<Sec<S, A>, FlowError<L>>
+    where
+        S: StaticLabel<L>,
+    {
+        let target = S::label();
+        if self.label.can_flow_to(&target) {
+            Ok(Sec::new(self.data))
+        } else {
+            Err(FlowError { from: self.label, to: target })
+        }
+    }
+}
+
+impl<S, A, L> From<Sec<S, A>> for DynSec<A, L>
+where
+    S: StaticLabel<L>,
+    L: Label,
+{
+    fn from(sec: Sec<S, A>) -> Self {
+        DynSec::from_sec(sec)
+    }
+}
+
+impl<S, A, L> TryFrom<DynSec<A, L>> for Sec<S, A>
+where
+    S: StaticLabel<L>,
+    L: Label,
+{
+    type Error = FlowError<L>;
+
+    fn try_from(data: DynSec<A, L>) -> Result<Self, Self::Error> {
+        data.into_sec()
+    }
+}
+
+impl<A, L> fmt::Debug for DynSec<A, L>
+where
+    L: Label,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DynSec<{:?}>(<redacted>)", self.label)
+    }
+}
+
+impl<A, L> fmt::Display for DynSec<A, L>
+where
+    L: Label,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DynSec<{:?}>(<redacted>)", self.label)
+    }
+}
@@ -0,0 +1,217 @@
+//! Fallible computations over `Sec`.
+//!
+//! A fallible computation on secret data gives a `Sec<S, Result<A, E>>`: like with labeled threads,
+//! whether it failed is just as secret as what it would have returned, and so is the error, which is often built from the data.
+//! `try_map` and `try_and_then` carry on with the value inside, short-circuiting on the first error like `?` does,
+//! so chains of fallible steps never nest `Sec<S, Result<Sec<S, Result<…>>>>`.
+//!
+//! To get back to plain `Result`s, and to propagate the error with `?`, `into_result` splits the outcome into
+//! `Result<Sec<S, A>, SecError<S, E>>`, like `transpose` but with an error type fit for `?`.
+//! As that reveals whether there was an error, it must be supplied with a `Clearance` for a security level &geq;
+//! the `Sec`'s, like `reveal`. The error itself stays labeled:
+//! `SecError` implements `std::error::Error`, but formats as `SecError<Level>(<redacted>)`,
+//! so it can be boxed, logged or returned from `main` without leaking anything.
+
+use std::error::Error;
+use std::fmt;
+
+use super::Sec;
+use super::clearance::Clearance;
+use super::redact::{short_type_name, Unredacted};
+use super::security_level as sl;
+
+/// An error labeled with a security level, which never formats its contents.
+///
+/// # Example
+/// ```
+/// use std::error::Error;
+/// use seclib::prelude::*;
+/// use seclib::fallible::SecError;
+///
+/// let high = Authority::acquire().unwrap().clearance::<High>();
+///
+/// let error: SecError<High, String> = SecError::new("no such user: alice".to_string());
+/// assert_eq!(error.to_string(), "SecError<High>(<redacted>)");
+///
+/// let boxed: Box<dyn Error> = Box::new(error.clone());
+/// assert_eq!(boxed.to_string(), "SecError<High>(<redacted>)");
+///
+/// assert_eq!(error.reveal(&high), "no such user: alice");
+/// ```
+#[derive(Clone)]
+pub struct SecError<S, E>
+where
+    S: sl::SecurityLevel,
+{
+    error: Sec<S, E>,
+}
+
+impl<S, E> SecError<S, E>
+where
+    S: sl::SecurityLevel,
+{
+    /// Constructor.
+    pub fn new(error: E) -> Self {
+        SecError { error: Sec::new(error) }
+    }
+
+    /// The error as a `Sec`, to compute with it without revealing it.
+    pub fn into_sec(self) -> Sec<S, E> {
+        self.error
+    }
+
+    /// Returns the error. Like `Sec::reveal`, it must be supplied with a `Clearance` for a security level &geq; the error's.
+    pub fn reveal<S2>(self, clearance: &Clearance<S2>) -> E
+    where
+        S2: sl::SecurityLevel<S> + sl::SecurityLevel,
+    {
+        self.error.reveal(clearance)
+    }
+
+    /// Gives formatting access to the error, like `Sec::unredacted`.
+    pub fn unredacted<'a, S2>(&'a self, clearance: &Clearance<S2>) -> Unredacted<'a, E>
+    where
+        S2: sl::SecurityLevel<S> + sl::SecurityLevel,
+    {
+        self.error.unredacted(clearance)
+    }
+}
+
+impl<S, E> From<Sec<S, E>> for SecError<S, E>
+where
+    S: sl::SecurityLevel,
+{
+    fn from(error: Sec<S, E>) -> Self {
+        SecError { error }
+    }
+}
+
+impl<S, E> fmt::Debug for SecError<S, E>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecError<{}>(<redacted>)", short_type_name::<S>())
+    }
+}
+
+impl<S, E> fmt::Display for SecError<S, E>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecError<{}>(<redacted>)", short_type_name::<S>())
+    }
+}
+
+/// There's no `source`, as that would format the labeled error in the clear.
+impl<S, E> Error for SecError<S, E> where S: sl::SecurityLevel {}
+
+impl<S, A, E> Sec<S, Result<A, E>>
+where
+    S: sl::SecurityLevel,
+{
+    /// Maps a fallible function over the value, if there is one, keeping any error at the same security level.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let pin: Sec<High, &str> = Sec::new("1234");
+    /// let scaled: Sec<High, Result<u16, String>> = pin
+    ///     .map(|p| p.parse::<u16>().map_err(|e| e.to_string()))
+    ///     .try_map(|p| p.checked_mul(100).ok_or(format!("{} is too large", p)));
+    ///
+    /// // the error mentions the pin, but is just as secret
+    /// assert_eq!(scaled.reveal(&high), Err("1234 is too large".to_string()));
+    /// ```
+    /// Whether it failed can't be seen without clearance:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let pin: Sec<High, &str> = Sec::new("12a4");
+    ///
+    /// if pin.map(|p| p.parse::<u32>()).try_map(|p| Ok(p * 2)).is_err() { // ERROR: no method named `is_err` found
+    ///     println!("the pin isn't a number");
+    /// }
+    /// ```
+    pub fn try_map<B, F>(self, f: F) -> Sec<S, Result<B, E>>
+    where
+        F: FnOnce(A) -> Result<B, E>,
+    {
+        self.map(|result| result.and_then(f))
+    }
+
+    /// Flat maps a fallible function returning a `Sec` over the value, if there is one,
+    /// resulting in a single `Sec` of the same security level.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// fn lookup(user: &str) -> Sec<High, Result<u32, String>> {
+    ///     Sec::new(if user == "alice" { Ok(52_000) } else { Err(format!("no such user: {}", user)) })
+    /// }
+    ///
+    /// let user: Sec<High, Result<&str, String>> = Sec::new(Ok("bob"));
+    /// let salary = user.try_and_then(lookup);
+    ///
+    /// assert_eq!(salary.reveal(&high), Err("no such user: bob".to_string()));
+    /// ```
+    pub fn try_and_then<B, F>(self, f: F) -> Sec<S, Result<B, E>>
+    where
+        F: FnOnce(A) -> Sec<S, Result<B, E>>,
+    {
+        self.and_then(|result| match result {
+            Ok(data) => f(data),
+            Err(error) => Sec::new(Err(error)),
+        })
+    }
+
+    /// Maps a function over the error, if there is one, e.g. to convert it for `?` like `From` would.
+    pub fn map_err<E2, F>(self, f: F) -> Sec<S, Result<A, E2>>
+    where
+        F: FnOnce(E) -> E2,
+    {
+        self.map(|result| result.map_err(f))
+    }
+
+    /// Splits the outcome into a `Result` of labeled value and labeled error, ready for `?`.
+    /// It's `transpose`, with the error wrapped in a `SecError`.
+    /// As that reveals whether there was an error, it must be supplied with a `Clearance` for a security level &geq; the `Sec`'s.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::error::Error;
+    /// use seclib::prelude::*;
+    ///
+    /// fn double(pin: Sec<High, &str>, high: &Clearance<High>) -> Result<Sec<High, u32>, Box<dyn Error>> {
+    ///     let pin = pin.map(|p| p.parse::<u32>()).into_result(high)?;
+    ///     Ok(pin.map(|p| p * 2))
+    /// }
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// assert_eq!(double(Sec::new("1234"), &high).unwrap().reveal(&high), 2468);
+    /// assert_eq!(double(Sec::new("12a4"), &high).unwrap_err().to_string(), "SecError<High>(<redacted>)");
+    /// ```
+    /// Asking with too low a clearance won't compile:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let pin: Sec<High, &str> = Sec::new("12a4");
+    ///
+    /// let result = pin.map(|p| p.parse::<u32>()).into_result(&authority.clearance::<Low>()); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// ```
+    pub fn into_result<S2>(self, clearance: &Clearance<S2>) -> Result<Sec<S, A>, SecError<S, E>>
+    where
+        S2: sl::SecurityLevel<S> + sl::SecurityLevel,
+    {
+        self.transpose(clearance).map_err(SecError::from)
+    }
+}
@@ -0,0 +1,276 @@
+//! Files labeled with a `SecurityLevel`.
+//!
+//! A `FileRegistry` maps paths (or whole directories) to security levels at runtime.
+//! Opening a `SecFile<S>` through it only succeeds if the path was registered at level `S`,
+//! after which the type system takes over: whatever is read from the file comes out as a `Sec<S, _>`,
+//! and only data of a level &leq; `S` can be written to it.
+//!
+//! This makes it impossible to, say, write a secret from the config directory into the public assets directory.
+//!
+//! Paths are compared component-wise, after resolving symbolic links in as much of them as exists,
+//! so a link in a public directory pointing into a secret one opens as secret, not as public.
+//! Relative paths are taken relative to the current directory. Paths containing `..` are rejected outright,
+//! as they could lead out of the directory they appear to be in.
+//!
+//! Links are resolved when registering and opening, so one created or swapped in after a file has been opened isn't noticed.
+//! Keep directories at different levels where untrusted code can't create links.
+
+use std::any::TypeId;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::marker::PhantomData;
+use std::path::{Component, Path, PathBuf};
+
+use super::Sec;
+use super::redact::short_type_name;
+use super::sec_io::SecIO;
+use super::security_level as sl;
+
+/// Reasons a `SecFile` can't be opened.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileError {
+    /// The path isn't covered by any registered path.
+    Unregistered(PathBuf),
+    /// The path is registered at a different security level than the one asked for.
+    WrongLevel {
+        path: PathBuf,
+        expected: String,
+        found: String,
+    },
+    /// The path contains a `..` component.
+    ParentDir(PathBuf),
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FileError::Unregistered(ref path) => write!(f, "{} has no registered security level", path.display()),
+            FileError::WrongLevel { ref path, ref expected, ref found } => {
+                write!(f, "{} is registered as {}, not {}", path.display(), found, expected)
+            }
+            FileError::ParentDir(ref path) => write!(f, "{} must not contain `..`", path.display()),
+        }
+    }
+}
+
+impl Error for FileError {}
+
+// Makes `path` absolute, and resolves symbolic links in the longest part of it that exists
+fn resolve(path: &Path) -> PathBuf {
+    let absolute = match env::current_dir() {
+        Ok(dir) => dir.join(path),
+        Err(_) => path.to_path_buf(),
+    };
+
+    let mut existing = absolute.as_path();
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest.iter().rev().fold(canonical, |resolved, name| resolved.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => return absolute.clone(),
+        }
+    }
+}
+
+struct Entry {
+    path: PathBuf,
+    level: TypeId,
+    level_name: String,
+}
+
+/// Maps paths to security levels.
+///
+/// A file gets the level of the longest registered path it lies within.
+#[derive(Default)]
+pub struct FileRegistry {
+    entries: Vec<Entry>,
+}
+
+impl FileRegistry {
+    /// Constructor. Creates an empty registry.
+    pub fn new() -> Self {
+        FileRegistry { entries: Vec::new() }
+    }
+
+    /// Registers `path`, and everything below it, at the security level `S`.
+    /// Registering the same path again replaces its level.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::file::FileRegistry;
+    ///
+    /// let mut registry = FileRegistry::new();
+    /// registry
+    ///     .register::<Low>("/srv/app/public")
+    ///     .register::<High>("/srv/app/config");
+    ///
+    /// assert!(registry.open::<Low>("/srv/app/public/index.html").is_ok());
+    /// assert!(registry.open::<Low>("/srv/app/config/secrets.toml").is_err());
+    /// ```
+    pub fn register<S>(&mut self, path: impl AsRef<Path>) -> &mut Self
+    where
+        S: sl::SecurityLevel + 'static,
+    {
+        let path = resolve(path.as_ref());
+        self.entries.retain(|entry| entry.path != path);
+        self.entries.push(Entry { path, level: TypeId::of::<S>(), level_name: short_type_name::<S>() });
+        self
+    }
+
+    /// Opens the file at `path` as a `SecFile<S>`, provided `path` is registered at level `S`.
+    pub fn open<S>(&self, path: impl AsRef<Path>) -> Result<SecFile<S>, FileError>
+    where
+        S: sl::SecurityLevel + 'static,
+    {
+        let path = path.as_ref();
+
+        if path.components().any(|c| c == Component::ParentDir) {
+            return Err(FileError::ParentDir(path.to_path_buf()));
+        }
+
+        let resolved = resolve(path);
+        let entry = self.entries.iter()
+            .filter(|entry| resolved.starts_with(&entry.path))
+            .max_by_key(|entry| entry.path.components().count())
+            .ok_or_else(|| FileError::Unregistered(path.to_path_buf()))?;
+
+        if entry.level != TypeId::of::<S>() {
+            return Err(FileError::WrongLevel {
+                path: path.to_path_buf(),
+                expected: short_type_name::<S>(),
+                found: entry.level_name.clone(),
+            });
+        }
+
+        Ok(SecFile { security_level: PhantomData, path: resolved })
+    }
+}
+
+/// A file labeled with the security level `S`.
+///
+/// Can only be created through `FileRegistry::open`.
+pub struct SecFile<S>
+where
+    S: sl::SecurityLevel,
+{
+    security_level: PhantomData<S>,
+    path: PathBuf,
+}
+
+impl<S> SecFile<S>
+where
+    S: sl::SecurityLevel,
+{
+    /// The path of the file, with symbolic links resolved.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the whole file. The outcome is labeled with the file's level, error and all,
+    /// so this can be done from a `SecIO` of any level: not even whether the file exists is revealed to it.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::fs;
+    /// use seclib::prelude::*;
+    /// use seclib::file::FileRegistry;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let high = authority.clearance::<High>();
+    /// let low = authority.clearance::<Low>();
+    ///
+    /// # let dir = std::env::temp_dir().join(format!("seclib-doc-read-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// # fs::write(dir.join("key"), "hunter2").unwrap();
+    /// let mut registry = FileRegistry::new();
+    /// registry.register::<High>(&dir);
+    ///
+    /// let key = registry.open::<High>(dir.join("key")).unwrap();
+    /// let program: SecIO<Low, _> = key.read_file();
+    ///
+    /// let contents: Sec<High, std::io::Result<Vec<u8>>> = program.run().reveal(&low);
+    /// assert_eq!(contents.reveal(&high).unwrap(), b"hunter2");
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn read_file<S2>(&self) -> SecIO<S2, Sec<S, io::Result<Vec<u8>>>>
+    where
+        S: 'static,
+        S2: sl::SecurityLevel,
+    {
+        let path = self.path.clone();
+        SecIO::from_fn(move || Sec::new(fs::read(path)))
+    }
+
+    /// Replaces the contents of the file with `data`, which has to be of a level &leq; the file's.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::fs;
+    /// use seclib::prelude::*;
+    /// use seclib::file::FileRegistry;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// # let dir = std::env::temp_dir().join(format!("seclib-doc-write-{}", std::process::id()));
+    /// # fs::create_dir_all(&dir).unwrap();
+    /// let mut registry = FileRegistry::new();
+    /// registry.register::<High>(&dir);
+    ///
+    /// let backup = registry.open::<High>(dir.join("backup")).unwrap();
+    /// let data: Sec<Low, &str> = Sec::new("nothing to see here");
+    ///
+    /// backup.write_file(data).run().reveal(&high).unwrap();
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    /// Writing secrets into a public file doesn't compile:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    /// use seclib::file::FileRegistry;
+    ///
+    /// let mut registry = FileRegistry::new();
+    /// registry.register::<Low>("/srv/app/public");
+    ///
+    /// let index = registry.open::<Low>("/srv/app/public/index.html").unwrap();
+    /// let data: Sec<High, &str> = Sec::new("hunter2");
+    ///
+    /// index.write_file(data); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// ```
+    pub fn write_file<S2, D>(&self, data: Sec<S2, D>) -> SecIO<S, io::Result<()>>
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+        D: AsRef<[u8]> + 'static,
+    {
+        let path = self.path.clone();
+        let Sec { data, .. } = data;
+        SecIO::from_fn(move || fs::write(path, data))
+    }
+}
+
+impl<S> Clone for SecFile<S>
+where
+    S: sl::SecurityLevel,
+{
+    fn clone(&self) -> Self {
+        SecFile { security_level: PhantomData, path: self.path.clone() }
+    }
+}
+
+impl<S> fmt::Debug for SecFile<S>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecFile<{}>({:?})", short_type_name::<S>(), self.path)
+    }
+}
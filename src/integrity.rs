@@ -0,0 +1,309 @@
+//! Integrity levels track how trustworthy data is, rather than how secret.
+//!
+//! Integrity is the dual of confidentiality: secret data may only flow *up*, to places at least as secret,
+//! whereas untrusted data may only flow *down*, to places that expect data at most as trustworthy.
+//! Trusted data can always be used where untrusted data is expected, but never the other way around.
+//!
+//! Data wrapped with `Int::new`, e.g. user input, is always `Untrusted`, and can only become `Trusted`
+//! by going through `endorse`, which runs it past a validator first.
+//! The only other source of trusted data is the program itself, through `trusted!`, which only takes literals.
+//! A sink that requires trusted data, like a SQL query or a shell command, just asks for an `Int<Trusted, _>`.
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+mod sealed {
+    pub trait Sealed<MoreTrusted> {}
+}
+
+/// IntegrityLevel encodes both the flow relation and the fact that something can **be** an integrity level.
+///
+/// `MoreTrusted` represents an integrity level whose data may flow to the current one.
+/// Like `SecurityLevel`, it's sealed, so no level can be declared more trusted than `Trusted` from outside the library.
+pub trait IntegrityLevel<MoreTrusted = Self>: Debug + sealed::Sealed<MoreTrusted>
+where
+    MoreTrusted: IntegrityLevel,
+{
+}
+
+/// Data that has been checked, or comes from the program itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trusted;
+
+/// Data from the outside world, e.g. user input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Untrusted;
+
+impl sealed::Sealed<Trusted> for Trusted {}
+impl sealed::Sealed<Trusted> for Untrusted {}
+impl sealed::Sealed<Untrusted> for Untrusted {}
+
+/// Implements T &#8849; T
+impl IntegrityLevel for Trusted {}
+
+/// Implements T &#8849; U, trusted data may be used where untrusted data is expected
+impl IntegrityLevel<Trusted> for Untrusted {}
+
+/// Implements U &#8849; U
+impl IntegrityLevel for Untrusted {}
+
+/// Wraps data with an `IntegrityLevel`.
+///
+/// Unlike `Sec`, an `Int` isn't secret, so its data can be looked at freely.
+/// What it prevents is *using* untrusted data where trusted data is required.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate seclib;
+///
+/// use seclib::prelude::*;
+///
+/// fn execute(query: Int<Trusted, String>) -> String {
+///     query.into_inner()
+/// }
+///
+/// fn main() {
+///     let query: Int<Trusted, String> = trusted!("SELECT * FROM users").map(String::from);
+///
+///     assert_eq!(execute(query), "SELECT * FROM users");
+/// }
+/// ```
+/// Untrusted input has to be endorsed before it gets anywhere near the database:
+/// ```compile_fail
+/// use seclib::prelude::*;
+///
+/// fn execute(query: Int<Trusted, String>) -> String {
+///     query.into_inner()
+/// }
+///
+/// let input: Int<Untrusted, String> = Int::new("'; DROP TABLE users; --".into());
+///
+/// execute(input); // ERROR: expected `Int<Trusted, String>`, found `Int<Untrusted, String>`
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Int<I, A>
+where
+    I: IntegrityLevel,
+{
+    integrity_level: PhantomData<I>,
+    data: A,
+}
+
+impl<A> Int<Untrusted, A> {
+    /// Constructor. Data from anywhere could be anything, so it starts out `Untrusted`.
+    ///
+    /// # Examples
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let input: Int<Untrusted, &str> = Int::new("users");
+    /// ```
+    /// It takes `endorse` to make it `Trusted`:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let input: Int<Trusted, &str> = Int::new("users"); // ERROR: expected `Int<Trusted, &str>`, found `Int<Untrusted, _>`
+    /// ```
+    pub fn new(data: A) -> Self {
+        Int::wrap(data)
+    }
+}
+
+impl<A> Int<Trusted, A> {
+    // Only for `trusted!`, which makes sure `data` is a literal
+    #[doc(hidden)]
+    pub fn __literal(data: A) -> Self {
+        Int::wrap(data)
+    }
+}
+
+/// Trusts a literal, which is part of the program itself, as an `Int<Trusted, _>`.
+///
+/// Anything else, even a `&'static str`, could have come from outside, e.g. by leaking a `String`, so it has to be endorsed.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate seclib;
+///
+/// use seclib::prelude::*;
+///
+/// fn main() {
+///     let table: Int<Trusted, &str> = trusted!("users");
+///     let limit: Int<Trusted, u32> = trusted!(100);
+/// }
+/// ```
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate seclib;
+///
+/// use seclib::prelude::*;
+///
+/// fn main() {
+///     let input: &'static str = String::from("'; DROP TABLE users; --").leak();
+///     let table: Int<Trusted, &str> = trusted!(input); // ERROR: no rules expected `input`
+/// }
+/// ```
+#[macro_export]
+macro_rules! trusted {
+    ($literal:literal) => {
+        $crate::integrity::Int::<$crate::integrity::Trusted, _>::__literal($literal)
+    };
+}
+
+impl<I, A> Int<I, A>
+where
+    I: IntegrityLevel,
+{
+    fn wrap(data: A) -> Self {
+        Int { data, integrity_level: PhantomData }
+    }
+
+    /// Maps a function over an `Int` and returns a new `Int` with the same integrity level.
+    pub fn map<B, F>(self, f: F) -> Int<I, B>
+    where
+        F: FnOnce(A) -> B,
+    {
+        Int::wrap(f(self.data))
+    }
+
+    /// Flat maps a function over an `Int`, resulting in a new `Int` of the same integrity level.
+    pub fn and_then<B, F>(self, f: F) -> Int<I, B>
+    where
+        F: FnOnce(A) -> Int<I, B>,
+    {
+        f(self.data)
+    }
+
+    /// Combines two `Int`s of the same integrity level with a function.
+    /// To combine trusted with untrusted data, `weaken` the trusted one first.
+    pub fn zip_with<B, C, F>(self, other: Int<I, B>, f: F) -> Int<I, C>
+    where
+        F: FnOnce(A, B) -> C,
+    {
+        Int::wrap(f(self.data, other.data))
+    }
+
+    /// Moves the data to a less trusted integrity level, which is always allowed.
+    ///
+    /// # Examples
+    /// ```
+    /// #[macro_use]
+    /// extern crate seclib;
+    ///
+    /// use seclib::prelude::*;
+    ///
+    /// fn main() {
+    ///     let table: Int<Trusted, &str> = trusted!("users");
+    ///     let result: Int<Untrusted, &str> = table.weaken(Untrusted);
+    /// }
+    /// ```
+    /// Going the other way requires `endorse`:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let input: Int<Untrusted, &str> = Int::new("users");
+    /// let result = input.weaken(Trusted); // ERROR: the trait `IntegrityLevel<Untrusted>` is not implemented for `Trusted`
+    /// ```
+    pub fn weaken<I2>(self, _: I2) -> Int<I2, A>
+    where
+        I2: IntegrityLevel<I> + IntegrityLevel,
+    {
+        Int::wrap(self.data)
+    }
+
+    /// Moves the data to any integrity level `I2`, provided `validator` accepts it.
+    /// The validator may also transform the data, e.g. parse or escape it.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// fn user_id(input: String) -> Result<u32, String> {
+    ///     input.parse().map_err(|_| format!("not a user id: {:?}", input))
+    /// }
+    ///
+    /// let input: Int<Untrusted, String> = Int::new("42".into());
+    /// let id: Int<Trusted, u32> = input.endorse(Trusted, user_id).unwrap();
+    /// assert_eq!(id.into_inner(), 42);
+    ///
+    /// let input: Int<Untrusted, String> = Int::new("42 OR 1=1".into());
+    /// assert!(input.endorse(Trusted, user_id).is_err());
+    /// ```
+    pub fn endorse<I2, B, E, F>(self, _: I2, validator: F) -> Result<Int<I2, B>, E>
+    where
+        I2: IntegrityLevel,
+        F: FnOnce(A) -> Result<B, E>,
+    {
+        validator(self.data).map(Int::wrap)
+    }
+
+    /// Returns a reference to the data.
+    pub fn get(&self) -> &A {
+        &self.data
+    }
+
+    /// Returns the data. Integrity doesn't restrict reading, only where the data can be used as an `Int`.
+    pub fn into_inner(self) -> A {
+        self.data
+    }
+}
+
+/// The least trusted integrity level that data of either level may flow to, at the type level.
+///
+/// Data combined from a trusted and an untrusted source is only as trustworthy as the untrusted one.
+pub trait IntegrityJoin<Other = Self>: IntegrityLevel + Sized
+where
+    Other: IntegrityLevel,
+{
+    type Output: IntegrityLevel + IntegrityLevel<Self> + IntegrityLevel<Other>;
+}
+
+/// The most trusted integrity level that may flow to either level, at the type level.
+pub trait IntegrityMeet<Other = Self>: IntegrityLevel + Sized
+where
+    Other: IntegrityLevel,
+{
+    type Output: IntegrityLevel;
+}
+
+/// Implements T &#8852; T = T
+impl IntegrityJoin for Trusted {
+    type Output = Trusted;
+}
+
+/// Implements T &#8852; U = U
+impl IntegrityJoin<Untrusted> for Trusted {
+    type Output = Untrusted;
+}
+
+/// Implements U &#8852; T = U
+impl IntegrityJoin<Trusted> for Untrusted {
+    type Output = Untrusted;
+}
+
+/// Implements U &#8852; U = U
+impl IntegrityJoin for Untrusted {
+    type Output = Untrusted;
+}
+
+/// Implements T &#8851; T = T
+impl IntegrityMeet for Trusted {
+    type Output = Trusted;
+}
+
+/// Implements T &#8851; U = T
+impl IntegrityMeet<Untrusted> for Trusted {
+    type Output = Trusted;
+}
+
+/// Implements U &#8851; T = T
+impl IntegrityMeet<Trusted> for Untrusted {
+    type Output = Trusted;
+}
+
+/// Implements U &#8851; U = U
+impl IntegrityMeet for Untrusted {
+    type Output = Untrusted;
+}
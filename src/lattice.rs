@@ -0,0 +1,548 @@
+//! Custom security lattices.
+//!
+//! Writing out every `impl SecurityLevel<X> for Y` by hand gets old quickly, as the ordering has to be
+//! reflexive and transitive, meaning a chain of `n` levels needs `n * (n + 1) / 2` impls.
+//! The `security_lattice!` macro declares the levels instead, and the ordering is worked out at the type level.
+//!
+//! Under the hood every level is a `LatticeLevel`, which knows the set of levels below it (its down-set) as a type-level bit set.
+//! `SecurityLevel<A>` is then implemented for `B` exactly when the down-set of `A` is a subset of the down-set of `B`.
+//! `Join` and `Meet` are found by searching the list of levels for the least upper and greatest lower bound respectively.
+//! In a partial order that isn't quite a lattice they settle for some upper (or lower) bound, which is still safe.
+//!
+//! Everything except `LatticeLevel` and the macro itself is an implementation detail.
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use super::security_level::{Join, Meet, SecurityLevel};
+use super::security_level::sealed::Sealed;
+
+/// A security level declared with `security_lattice!`.
+///
+/// There's no need to implement this by hand, the macro takes care of it.
+pub trait LatticeLevel: Debug {
+    /// Marker tying together the levels of a single lattice. It's the first level declared.
+    type Lattice;
+    /// The set of levels &leq; this one.
+    #[doc(hidden)]
+    type Down: IsSubset<Self::Down, Output = True>;
+    /// Position of the level in its `security_lattice!` declaration.
+    const INDEX: usize;
+}
+
+impl<A, B> Sealed<A> for B
+where
+    A: LatticeLevel,
+    B: LatticeLevel<Lattice = A::Lattice>,
+    A::Down: IsSubset<B::Down, Output = True>,
+{
+}
+
+/// Implements L1 &leq; L2 for any two levels of the same lattice where the down-set of L1 is contained in the down-set of L2.
+impl<A, B> SecurityLevel<A> for B
+where
+    A: LatticeLevel,
+    B: LatticeLevel<Lattice = A::Lattice>,
+    A::Down: IsSubset<B::Down, Output = True>,
+{
+}
+
+/// Implements L1 &vee; L2 for any two levels of the same lattice that have an upper bound.
+impl<A, B> Join<B> for A
+where
+    A: LatticeLevel + SecurityLevel,
+    B: LatticeLevel<Lattice = A::Lattice> + SecurityLevel,
+    A::Lattice: Levels,
+    <A::Lattice as Levels>::List: FindJoin<A, B, NoBound<Full>>,
+    <<A::Lattice as Levels>::List as FindJoin<A, B, NoBound<Full>>>::Output: SecurityLevel + SecurityLevel<A> + SecurityLevel<B>,
+{
+    type Output = <<A::Lattice as Levels>::List as FindJoin<A, B, NoBound<Full>>>::Output;
+}
+
+/// Implements L1 &wedge; L2 for any two levels of the same lattice that have a lower bound.
+impl<A, B> Meet<B> for A
+where
+    A: LatticeLevel + SecurityLevel,
+    B: LatticeLevel<Lattice = A::Lattice> + SecurityLevel,
+    A::Lattice: Levels,
+    <A::Lattice as Levels>::List: FindMeet<A, B, NoBound<Nil>>,
+    <<A::Lattice as Levels>::List as FindMeet<A, B, NoBound<Nil>>>::Output: SecurityLevel,
+{
+    type Output = <<A::Lattice as Levels>::List as FindMeet<A, B, NoBound<Nil>>>::Output;
+}
+
+/// All the levels of a lattice as a list of nested pairs, implemented for the lattice marker by `security_lattice!`.
+#[doc(hidden)]
+pub trait Levels {
+    type List;
+}
+
+/// Anything with a down-set, meaning the levels themselves as well as the starting point of a search.
+#[doc(hidden)]
+pub trait Candidate {
+    type Down;
+}
+
+impl<T> Candidate for T
+where
+    T: LatticeLevel,
+{
+    type Down = T::Down;
+}
+
+/// Placeholder for a bound not found (yet).
+/// Its down-set makes any level look better: `Full` when looking for the least upper bound, `Nil` for the greatest lower bound.
+#[doc(hidden)]
+pub struct NoBound<D>(PhantomData<D>);
+
+impl<D> Candidate for NoBound<D> {
+    type Down = D;
+}
+
+/// Type-level `<=` between anything with a down-set.
+#[doc(hidden)]
+pub trait Le<Rhs> {
+    type Output: Bool;
+}
+
+impl<X, Y> Le<Y> for X
+where
+    X: Candidate,
+    Y: Candidate,
+    X::Down: IsSubset<Y::Down>,
+{
+    type Output = <X::Down as IsSubset<Y::Down>>::Output;
+}
+
+/// Searches a list of levels for the least upper bound of `A` and `B`.
+#[doc(hidden)]
+pub trait FindJoin<A, B, Best> {
+    type Output;
+}
+
+impl<A, B, Best> FindJoin<A, B, Best> for () {
+    type Output = Best;
+}
+
+// `C` replaces `Best` if A <= C, B <= C, and C <= Best
+impl<C, Rest, A, B, Best> FindJoin<A, B, Best> for (C, Rest)
+where
+    A: Le<C>,
+    B: Le<C>,
+    C: Le<Best>,
+    (<A as Le<C>>::Output, <B as Le<C>>::Output, <C as Le<Best>>::Output): AllThen<C, Best>,
+    Rest: FindJoin<A, B, <(<A as Le<C>>::Output, <B as Le<C>>::Output, <C as Le<Best>>::Output) as AllThen<C, Best>>::Output>,
+{
+    type Output = <Rest as FindJoin<
+        A,
+        B,
+        <(<A as Le<C>>::Output, <B as Le<C>>::Output, <C as Le<Best>>::Output) as AllThen<C, Best>>::Output,
+    >>::Output;
+}
+
+/// Searches a list of levels for the greatest lower bound of `A` and `B`.
+#[doc(hidden)]
+pub trait FindMeet<A, B, Best> {
+    type Output;
+}
+
+impl<A, B, Best> FindMeet<A, B, Best> for () {
+    type Output = Best;
+}
+
+// `C` replaces `Best` if C <= A, C <= B, and Best <= C
+impl<C, Rest, A, B, Best> FindMeet<A, B, Best> for (C, Rest)
+where
+    C: Le<A> + Le<B>,
+    Best: Le<C>,
+    (<C as Le<A>>::Output, <C as Le<B>>::Output, <Best as Le<C>>::Output): AllThen<C, Best>,
+    Rest: FindMeet<A, B, <(<C as Le<A>>::Output, <C as Le<B>>::Output, <Best as Le<C>>::Output) as AllThen<C, Best>>::Output>,
+{
+    type Output = <Rest as FindMeet<
+        A,
+        B,
+        <(<C as Le<A>>::Output, <C as Le<B>>::Output, <Best as Le<C>>::Output) as AllThen<C, Best>>::Output,
+    >>::Output;
+}
+
+/// Used by `security_lattice!` to get at the down-set of a lower level, but only if it was declared earlier.
+///
+/// Without this check a cycle would send the compiler into an endless loop computing down-sets.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has to be declared before a level can be placed above it",
+    label = "levels are declared bottom-up, which also rules out cycles"
+)]
+pub trait DeclaredBefore<const BEFORE: bool> {
+    type Down;
+}
+
+impl<T> DeclaredBefore<true> for T
+where
+    T: LatticeLevel,
+{
+    type Down = T::Down;
+}
+
+/// Used by `security_lattice!` to make sure a level is only placed above levels of its own lattice.
+#[doc(hidden)]
+pub const fn __assert_same_lattice<A, B>()
+where
+    A: LatticeLevel,
+    B: LatticeLevel<Lattice = A::Lattice>,
+{
+}
+
+// Type-level booleans
+
+#[doc(hidden)]
+pub struct True;
+
+#[doc(hidden)]
+pub struct False;
+
+#[doc(hidden)]
+pub trait Bool {}
+
+impl Bool for True {}
+
+impl Bool for False {}
+
+#[doc(hidden)]
+pub trait And<Rhs> {
+    type Output: Bool;
+}
+
+impl And<True> for True {
+    type Output = True;
+}
+
+impl And<False> for True {
+    type Output = False;
+}
+
+impl<Rhs> And<Rhs> for False {
+    type Output = False;
+}
+
+/// `Then` if all three are `True`, otherwise `Else`.
+#[doc(hidden)]
+pub trait AllThen<Then, Else> {
+    type Output;
+}
+
+impl<Then, Else, X, Y> AllThen<Then, Else> for (False, X, Y) {
+    type Output = Else;
+}
+
+impl<Then, Else, Y> AllThen<Then, Else> for (True, False, Y) {
+    type Output = Else;
+}
+
+impl<Then, Else> AllThen<Then, Else> for (True, True, False) {
+    type Output = Else;
+}
+
+impl<Then, Else> AllThen<Then, Else> for (True, True, True) {
+    type Output = Then;
+}
+
+// Type-level bit sets, stored as a list of bits with the lowest index first
+
+#[doc(hidden)]
+pub struct B0;
+
+#[doc(hidden)]
+pub struct B1;
+
+#[doc(hidden)]
+pub struct Nil;
+
+#[doc(hidden)]
+pub struct Cons<H, T>(PhantomData<(H, T)>);
+
+/// The set of everything, which every bit set is a subset of.
+#[doc(hidden)]
+pub struct Full;
+
+#[doc(hidden)]
+pub trait BitOr<Rhs> {
+    type Output;
+}
+
+impl BitOr<B0> for B0 {
+    type Output = B0;
+}
+
+impl BitOr<B1> for B0 {
+    type Output = B1;
+}
+
+impl<Rhs> BitOr<Rhs> for B1 {
+    type Output = B1;
+}
+
+#[doc(hidden)]
+pub trait BitLe<Rhs> {
+    type Output: Bool;
+}
+
+impl<Rhs> BitLe<Rhs> for B0 {
+    type Output = True;
+}
+
+impl BitLe<B0> for B1 {
+    type Output = False;
+}
+
+impl BitLe<B1> for B1 {
+    type Output = True;
+}
+
+#[doc(hidden)]
+pub trait Union<Rhs> {
+    type Output;
+}
+
+impl<Rhs> Union<Rhs> for Nil {
+    type Output = Rhs;
+}
+
+impl<H, T> Union<Nil> for Cons<H, T> {
+    type Output = Cons<H, T>;
+}
+
+impl<H1, T1, H2, T2> Union<Cons<H2, T2>> for Cons<H1, T1>
+where
+    H1: BitOr<H2>,
+    T1: Union<T2>,
+{
+    type Output = Cons<H1::Output, T1::Output>;
+}
+
+#[doc(hidden)]
+pub trait IsSubset<Rhs> {
+    type Output: Bool;
+}
+
+impl<Rhs> IsSubset<Rhs> for Nil {
+    type Output = True;
+}
+
+impl<T> IsSubset<Nil> for Cons<B0, T>
+where
+    T: IsSubset<Nil>,
+{
+    type Output = T::Output;
+}
+
+impl<T> IsSubset<Nil> for Cons<B1, T> {
+    type Output = False;
+}
+
+impl<H, T> IsSubset<Full> for Cons<H, T> {
+    type Output = True;
+}
+
+impl<H1, T1, H2, T2> IsSubset<Cons<H2, T2>> for Cons<H1, T1>
+where
+    H1: BitLe<H2>,
+    T1: IsSubset<T2>,
+    H1::Output: And<T1::Output>,
+{
+    type Output = <H1::Output as And<T1::Output>>::Output;
+}
+
+/// Declares a custom security lattice.
+///
+/// The levels are declared as unit structs, each implementing `SecurityLevel` for every level at or below it.
+/// This includes the reflexive pairs, as well as any pairs implied by transitivity.
+/// Any two levels with an upper bound also get to `Join`, and any two with a lower bound get to `Meet`.
+///
+/// A chain of levels is written out as a list of orderings:
+/// ```
+/// #[macro_use]
+/// extern crate seclib;
+///
+/// use seclib::prelude::*;
+///
+/// security_lattice! {
+///     pub Public < Internal < Secret < TopSecret
+/// }
+///
+/// fn main() {
+///     let authority = Authority::acquire().unwrap();
+///
+///     let memo: Sec<Internal, &str> = Sec::new("Lunch is at noon");
+///     let memo = memo.lift(TopSecret); // Internal <= TopSecret, by transitivity
+///
+///     assert_eq!(memo.reveal(&authority.clearance::<TopSecret>()), "Lunch is at noon");
+///
+///     let notice: Sec<Public, &str> = Sec::new("We're hiring");
+///     assert_eq!(notice.reveal(&authority.clearance::<Public>()), "We're hiring"); // Public <= Public
+/// }
+/// ```
+/// Going down the chain is still a compile error:
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate seclib;
+///
+/// use seclib::prelude::*;
+///
+/// security_lattice! {
+///     pub Public < Internal < Secret < TopSecret
+/// }
+///
+/// fn main() {
+///     let authority = Authority::acquire().unwrap();
+///
+///     let plans: Sec<Secret, &str> = Sec::new("Acquire the competition");
+///     plans.reveal(&authority.clearance::<Internal>()); // ERROR: Secret is not <= Internal
+/// }
+/// ```
+/// Lattices that aren't chains are declared one level at a time, each listing the levels directly below it after a `>`.
+/// Here is the diamond lattice, where `Hr` and `Finance` are incomparable:
+/// ```
+/// #[macro_use]
+/// extern crate seclib;
+///
+/// use seclib::prelude::*;
+///
+/// security_lattice! {
+///     pub Staff;
+///     pub Hr > Staff;
+///     pub Finance > Staff;
+///     pub Board > Hr, Finance;
+/// }
+///
+/// fn main() {
+///     let payroll: Sec<Finance, u32> = Sec::new(1_000_000);
+///     let reviews: Sec<Hr, u32> = Sec::new(12);
+///
+///     let board = Authority::acquire().unwrap().clearance::<Board>();
+///
+///     assert_eq!(payroll.reveal(&board), 1_000_000);
+///     assert_eq!(reviews.reveal(&board), 12);
+/// }
+/// ```
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate seclib;
+///
+/// use seclib::prelude::*;
+///
+/// security_lattice! {
+///     pub Staff;
+///     pub Hr > Staff;
+///     pub Finance > Staff;
+///     pub Board > Hr, Finance;
+/// }
+///
+/// fn main() {
+///     let hr = Authority::acquire().unwrap().clearance::<Hr>();
+///
+///     let payroll: Sec<Finance, u32> = Sec::new(1_000_000);
+///     payroll.reveal(&hr); // ERROR: Finance and Hr are incomparable
+/// }
+/// ```
+/// Levels have to be declared before anything is placed above them. That makes cycles impossible to write,
+/// and trying to is a compile error saying as much:
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate seclib;
+///
+/// security_lattice! {
+///     pub Alpha > Beta; // ERROR: `Beta` has to be declared before a level can be placed above it
+///     pub Beta > Alpha;
+/// }
+///
+/// fn main() {}
+/// ```
+/// Declaring the same level twice fails too, as it defines the struct twice:
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate seclib;
+///
+/// security_lattice! {
+///     pub Public < Internal < Public // ERROR: the name `Public` is defined multiple times
+/// }
+///
+/// fn main() {}
+/// ```
+#[macro_export]
+macro_rules! security_lattice {
+    // Turns a chain `A < B < C` into the one-level-at-a-time form `A; B > A; C > B;`
+    (@chain [$vis:vis] [$($out:tt)*] $prev:ident) => {
+        $crate::security_lattice!($($out)*);
+    };
+    (@chain [$vis:vis] [$($out:tt)*] $prev:ident < $(#[$attr:meta])* $next:ident $($rest:tt)*) => {
+        $crate::security_lattice!(@chain [$vis] [$($out)* $(#[$attr])* $vis $next > $prev;] $next $($rest)*);
+    };
+
+    // Declares the levels one by one. `$zeros` holds a `B0` for every level declared so far, and `$names` their names.
+    (@levels [$first:ident] [$($zeros:ident)*] [$($names:ident)*]) => {
+        impl $crate::lattice::Levels for $first {
+            type List = $crate::security_lattice!(@list $($names)*);
+        }
+    };
+    (@levels [$first:ident] [$($zeros:ident)*] [$($names:ident)*]
+        $(#[$attr:meta])* $vis:vis $name:ident $(> $($lower:ident),+)*; $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq)]
+        $vis struct $name;
+
+        impl $crate::lattice::LatticeLevel for $name {
+            type Lattice = $first;
+            type Down = $crate::security_lattice!(
+                @down $crate::security_lattice!(@bit $($zeros)*); [$(stringify!($zeros)),*] $($($lower),+),*
+            );
+            const INDEX: usize = <[&str]>::len(&[$(stringify!($zeros)),*]);
+        }
+
+        $($(
+            const _: () = $crate::lattice::__assert_same_lattice::<$name, $lower>();
+        )+)*
+
+        $crate::security_lattice!(@levels [$first] [$($zeros)* B0] [$($names)* $name] $($rest)*);
+    };
+
+    // The levels as a list of nested pairs
+    (@list) => {
+        ()
+    };
+    (@list $name:ident $($names:ident)*) => {
+        ($name, $crate::security_lattice!(@list $($names)*))
+    };
+
+    // The singleton bit set of the level with as many `B0`s in front of it
+    (@bit) => {
+        $crate::lattice::Cons<$crate::lattice::B1, $crate::lattice::Nil>
+    };
+    (@bit $zero:ident $($zeros:ident)*) => {
+        $crate::lattice::Cons<$crate::lattice::B0, $crate::security_lattice!(@bit $($zeros)*)>
+    };
+
+    // The union of a bit set with the down-sets of the given levels, which all need to come before `$index`
+    (@down $acc:ty; [$($index:tt)*]) => {
+        $acc
+    };
+    (@down $acc:ty; [$($index:tt)*] $lower:ident $(, $rest:ident)*) => {
+        $crate::security_lattice!(
+            @down <$acc as $crate::lattice::Union<
+                <$lower as $crate::lattice::DeclaredBefore<{
+                    <$lower as $crate::lattice::LatticeLevel>::INDEX < <[&str]>::len(&[$($index)*])
+                }>>::Down
+            >>::Output;
+            [$($index)*] $($rest),*
+        )
+    };
+
+    // Entry points
+    ($(#[$attr:meta])* $vis:vis $first:ident $(< $(#[$rattr:meta])* $rest:ident)+ $(;)*) => {
+        $crate::security_lattice!(@chain [$vis] [$(#[$attr])* $vis $first;] $first $(< $(#[$rattr])* $rest)+);
+    };
+    ($(#[$attr:meta])* $vis:vis $first:ident $(> $($lower:ident),+)*; $($rest:tt)*) => {
+        $crate::security_lattice!(@levels [$first] [] [] $(#[$attr])* $vis $first $(> $($lower),+)*; $($rest)*);
+    };
+}
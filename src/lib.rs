@@ -7,12 +7,68 @@ pub mod security_level;
 
 pub mod prelude;
 
+pub mod redact;
+
+pub mod compare;
+
+#[macro_use]
+pub mod lattice;
+
+pub mod sec_io;
+
+pub mod file;
+
+pub mod audit;
+
+pub mod declassify;
+
+pub mod dynamic;
+
+pub mod lio;
+
+pub mod traverse;
+
+pub mod integrity;
+
+pub mod product;
+
+pub mod principal;
+
+pub mod dlm;
+
+pub mod dc_label;
+
+pub mod testing;
+
+pub mod clearance;
+
+pub mod sec_ref;
+
+pub mod channel;
+
+pub mod sec_thread;
+
+pub mod zeroize;
+
+pub mod constant_time;
+
+pub mod timing;
+
+pub mod fallible;
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64")))]
+pub mod locked;
+
 use security_level as sl;
+use clearance::Clearance;
 
 /// The Sec monad which wraps any kind of data with a `SecurityLevel`.
 /// It provides means of securely modifying the internal data via `map` and `and_then`, 
 /// while also allowing the user to lift/promote the security level, or even discard it entirely.
-#[derive(Debug, PartialEq, Clone)]
+///
+/// Formatting a `Sec` never shows its data, see the `redact` module.
+/// Neither does comparing it, see the `compare` module.
+#[derive(Clone)]
 pub struct Sec<S, A>
 where
     S: sl::SecurityLevel, // s must be a security level
@@ -36,13 +92,13 @@ where
     /// # Example
     /// ```
     /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
     /// 
     /// let data: Sec<High, String> = Sec::new("I'm Safe".into());
     /// let result = data.map(|s| format!("{}!", s));
     /// 
-    /// let expected: Sec<High, String> = Sec::new("I'm Safe!".into());
-    /// 
-    /// assert_eq!(result, expected);
+    /// assert_eq!(result.reveal(&high), "I'm Safe!");
     /// ```
     pub fn map<B, F>(self, f: F) -> Sec<S, B> 
     where 
@@ -62,6 +118,8 @@ where
     /// # Example
     /// ```
     /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
     /// 
     /// fn func(i: i32) -> Sec<High, i32> {
     ///     (i + 2).into()
@@ -69,9 +127,8 @@ where
     /// 
     /// let data: Sec<High, i32> = 4.into();
     /// let result = data.and_then(func);
-    /// let expected: Sec<High, i32> = 6.into();
     /// 
-    /// assert_eq!(result, expected);
+    /// assert_eq!(result.reveal(&high), 6);
     /// ```
     pub fn and_then<B, F>(self, f: F) -> Sec<S, B> 
     where 
@@ -81,31 +138,76 @@ where
         f(data)
     }
     
+    /// Combines two `Sec`s with a function, resulting in a new `Sec` at the join (least upper bound) of both their security levels.
+    /// 
+    /// # Examples
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    /// 
+    /// let name: Sec<Low, &str> = Sec::new("Alice");
+    /// let salary: Sec<High, u32> = Sec::new(52_000);
+    /// 
+    /// let result: Sec<High, String> = name.zip_with(salary, |n, s| format!("{} earns {}", n, s));
+    /// 
+    /// assert_eq!(result.reveal(&high), "Alice earns 52000");
+    /// ```
+    /// The combined data is at least as secret as either half, so it can't end up in a `Sec<Low, _>`:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    /// 
+    /// let name: Sec<Low, &str> = Sec::new("Alice");
+    /// let salary: Sec<High, u32> = Sec::new(52_000);
+    /// 
+    /// let result: Sec<Low, String> = name.zip_with(salary, |n, s| format!("{} earns {}", n, s)); // ERROR: expected `Low`, found `High`
+    /// ```
+    pub fn zip_with<S2, B, C, F>(self, other: Sec<S2, B>, f: F) -> Sec<sl::JoinOf<S, S2>, C>
+    where
+        S: sl::Join<S2>,
+        S2: sl::SecurityLevel,
+        F: FnOnce(A, B) -> C
+    {
+        Sec::new(f(self.data, other.data))
+    }
+
     /// Reveal returns the value from within a `Sec`.
-    /// Note that in order to do so, it must be supplied with a security level &geq; the `Sec`'s
+    /// Note that in order to do so, it must be supplied with a `Clearance` for a security level &geq; the `Sec`'s
     /// 
     /// # Examples
     /// The following example shows how you'd get the value out:
     /// ```
     /// use seclib::prelude::*;
     /// 
+    /// let authority = Authority::acquire().unwrap();
+    /// 
     /// // Data safely stored within a Sec
     /// let data: Sec<High, String> = Sec::new("Attack at Dawn!".into());
     /// 
-    /// let output = data.reveal(High); // `data` is now moved and no longer available
+    /// let output = data.reveal(&authority.clearance::<High>()); // `data` is now moved and no longer available
     /// assert_eq!(output, "Attack at Dawn!".to_string());
     /// ```
-    /// The following example showcases what would happen if the wrong security level were to be used:
+    /// The following example showcases what would happen if the wrong clearance were to be used:
     /// ```compile_fail
     /// use seclib::prelude::*;
     /// 
+    /// let authority = Authority::acquire().unwrap();
+    /// 
     /// // Data safely stored within a Sec
     /// let data: Sec<High, String> = Sec::new("Attack at Dawn!".into());
     /// 
-    /// let output = data.reveal(Low); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// let output = data.reveal(&authority.clearance::<Low>()); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
     /// assert_eq!(output, "Attack at Dawn!".to_string());
     /// ```
-    pub fn reveal<S2>(self, _: S2) -> A 
+    /// The bare level won't do either, as anyone can write `High`:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    /// 
+    /// let data: Sec<High, String> = Sec::new("Attack at Dawn!".into());
+    /// 
+    /// let output = data.reveal(High); // ERROR: expected `&Clearance<_>`, found `High`
+    /// ```
+    pub fn reveal<S2>(self, _: &Clearance<S2>) -> A 
     where 
         S2: sl::SecurityLevel<S> + sl::SecurityLevel
     {
@@ -118,22 +220,24 @@ where
     /// Converting from low to high works as expected:
     /// ```
     /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
     /// 
     /// let data: Sec<Low, String> = Sec::new("Attack at midnight.".into());
-    /// let result = data.lift(High); // `data` is now of type `Sec<High, String>`
-    /// let expected: Sec<High, String> = Sec::new("Attack at midnight.".into());
+    /// let result: Sec<High, String> = data.lift(High); // `data` is now of type `Sec<High, String>`
     /// 
-    /// assert_eq!(result, expected);
+    /// assert_eq!(result.reveal(&high), "Attack at midnight.");
     /// ```
     /// However, trying to convert from high to low results in a compile error:
     /// ```compile_fail
     /// use seclib::prelude::*;
+    ///
+    /// let low = Authority::acquire().unwrap().clearance::<Low>();
     /// 
     /// let data: Sec<High, String> = Sec::new("Attack at midnight.".into());
     /// let result = data.lift(Low); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
-    /// let expected: Sec<Low, String> = Sec::new("Attack at midnight.".into());
     /// 
-    /// assert_eq!(result, expected);
+    /// assert_eq!(result.reveal(&low), "Attack at midnight.");
     /// ```
     pub fn lift<S2>(self, _: S2) -> Sec<S2, A>
     where
@@ -0,0 +1,293 @@
+//! Dynamic information flow control with a floating label, in the style of Haskell's LIO.
+//!
+//! Code running inside an `Lio` context has a *current label*, which is an upper bound on everything it has looked at.
+//! Unlabeling data raises the current label to the join of the two, but never above the context's *clearance*,
+//! which is set by a `DynClearance`.
+//! Writing, or labeling new data, is only allowed at or above the current label,
+//! so nothing read can end up anywhere less secret than it came from.
+//!
+//! Violations are reported at runtime, as a `LioError`. Whether a computation ran into one may depend on what it looked at,
+//! so the errors coming out of `run` and `to_labeled` are labeled along with their results.
+//! Statically labeled `Sec` values can be unlabeled too, via their `StaticLabel` counterpart.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+
+use super::Sec;
+use super::clearance::DynClearance;
+use super::dynamic::{DynSec, FlowError, Label, StaticLabel};
+
+/// Reasons an `Lio` operation can fail.
+#[derive(Debug)]
+pub enum LioError<L> {
+    /// The current label would have to rise above the clearance.
+    ExceedsClearance { label: L, clearance: L },
+    /// The target of a write, or a new label, is below the current label.
+    BelowCurrent { current: L, target: L },
+    /// Writing to a sink failed.
+    Io(io::Error),
+}
+
+impl<L> fmt::Display for LioError<L>
+where
+    L: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LioError::ExceedsClearance { ref label, ref clearance } => {
+                write!(f, "label {:?} exceeds the clearance {:?}", label, clearance)
+            }
+            LioError::BelowCurrent { ref current, ref target } => {
+                write!(f, "label {:?} is below the current label {:?}", target, current)
+            }
+            LioError::Io(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl<L> Error for LioError<L> where L: fmt::Debug {}
+
+impl<L> From<io::Error> for LioError<L> {
+    fn from(error: io::Error) -> Self {
+        LioError::Io(error)
+    }
+}
+
+/// The outcome of a computation run by `Lio::run` or `Lio::to_labeled`, error and all, labeled with a runtime label.
+pub type Labeled<A, L> = DynSec<Result<A, LioError<L>>, L>;
+
+/// A writer labeled with a runtime label.
+///
+/// Like a `Sink`, it never formats its writer, which may hold whatever was written to it.
+pub struct LioSink<L, W>
+where
+    L: Label,
+{
+    label: L,
+    writer: W,
+}
+
+impl<L, W> LioSink<L, W>
+where
+    L: Label,
+    W: Write,
+{
+    /// Constructor. Labels `writer` with `label`.
+    pub fn new(label: L, writer: W) -> Self {
+        LioSink { label, writer }
+    }
+
+    /// The label of the sink.
+    pub fn label(&self) -> &L {
+        &self.label
+    }
+
+    /// Gives back the underlying writer, with everything written to it at the sink's label.
+    /// Like `DynSec::reveal`, it must be supplied with a `DynClearance` the sink's label can flow to.
+    pub fn into_inner(self, clearance: &DynClearance<L>) -> Result<W, FlowError<L>> {
+        if self.label.can_flow_to(clearance.label()) {
+            Ok(self.writer)
+        } else {
+            Err(FlowError { from: self.label, to: clearance.label().clone() })
+        }
+    }
+}
+
+impl<L, W> fmt::Debug for LioSink<L, W>
+where
+    L: Label,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LioSink<{:?}>(..)", self.label)
+    }
+}
+
+/// An LIO execution context, tracking the current label and the clearance.
+///
+/// It isn't `Clone`: a copy taken before unlabeling something would still be at the old current label,
+/// and could write what was unlabeled to a sink below it.
+#[derive(Debug)]
+pub struct Lio<L>
+where
+    L: Label,
+{
+    current: L,
+    clearance: L,
+}
+
+impl<L> Lio<L>
+where
+    L: Label,
+{
+    /// Constructor. Starts out at the label `current`, which may rise as high as the label of `clearance`.
+    ///
+    /// As code in the context can unlabel anything up to its clearance, that takes a `DynClearance` for it,
+    /// which like any clearance can only be minted by the process' `Authority`.
+    pub fn new(current: L, clearance: &DynClearance<L>) -> Result<Self, LioError<L>> {
+        let clearance = clearance.label().clone();
+        if current.can_flow_to(&clearance) {
+            Ok(Lio { current, clearance })
+        } else {
+            Err(LioError::ExceedsClearance { label: current, clearance })
+        }
+    }
+
+    /// The current label.
+    pub fn current(&self) -> &L {
+        &self.current
+    }
+
+    /// The clearance.
+    pub fn clearance(&self) -> &L {
+        &self.clearance
+    }
+
+    /// Runs `f` in this context. Its outcome is labeled with wherever the current label ended up,
+    /// error and all, as whether it failed may depend on what it looked at.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::dynamic::Level;
+    /// use seclib::lio::Lio;
+    ///
+    /// let high = Authority::acquire().unwrap().dyn_clearance(Level::High);
+    /// let secret: Sec<High, i32> = Sec::new(41);
+    ///
+    /// let result = Lio::new(Level::Low, &high).unwrap().run(|lio| Ok(lio.unlabel_sec(secret)? + 1));
+    ///
+    /// assert_eq!(result.label(), &Level::High);
+    /// assert_eq!(result.reveal(&high).unwrap().unwrap(), 42);
+    /// ```
+    pub fn run<A, F>(mut self, f: F) -> Labeled<A, L>
+    where
+        F: FnOnce(&mut Lio<L>) -> Result<A, LioError<L>>,
+    {
+        let outcome = f(&mut self);
+        DynSec::new(self.current, outcome)
+    }
+
+    /// Raises the current label to its join with `label`.
+    pub fn taint(&mut self, label: &L) -> Result<(), LioError<L>> {
+        let raised = self.current.join(label);
+        if raised.can_flow_to(&self.clearance) {
+            self.current = raised;
+            Ok(())
+        } else {
+            Err(LioError::ExceedsClearance { label: raised, clearance: self.clearance.clone() })
+        }
+    }
+
+    // Checks current <= label <= clearance
+    fn guard(&self, label: &L) -> Result<(), LioError<L>> {
+        if !self.current.can_flow_to(label) {
+            Err(LioError::BelowCurrent { current: self.current.clone(), target: label.clone() })
+        } else if !label.can_flow_to(&self.clearance) {
+            Err(LioError::ExceedsClearance { label: label.clone(), clearance: self.clearance.clone() })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Takes the data out of a `DynSec`, raising the current label to cover it.
+    pub fn unlabel<A>(&mut self, value: DynSec<A, L>) -> Result<A, LioError<L>> {
+        self.taint(&value.label)?;
+        Ok(value.data)
+    }
+
+    /// Takes the data out of a `Sec`, raising the current label to cover its level.
+    pub fn unlabel_sec<S, A>(&mut self, value: Sec<S, A>) -> Result<A, LioError<L>>
+    where
+        S: StaticLabel<L>,
+    {
+        self.taint(&S::label())?;
+        Ok(value.data)
+    }
+
+    /// Labels `data` with `label`, which must be between the current label and the clearance.
+    pub fn label<A>(&self, label: L, data: A) -> Result<DynSec<A, L>, LioError<L>> {
+        self.guard(&label)?;
+        Ok(DynSec::new(label, data))
+    }
+
+    /// Wraps `data` in a `Sec<S, A>`, whose level must be between the current label and the clearance.
+    pub fn label_sec<S, A>(&self, data: A) -> Result<Sec<S, A>, LioError<L>>
+    where
+        S: StaticLabel<L>,
+    {
+        self.guard(&S::label())?;
+        Ok(Sec::new(data))
+    }
+
+    /// Writes `data` to `sink`, whose label must be between the current label and the clearance.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::dynamic::Level;
+    /// use seclib::lio::{Lio, LioError, LioSink};
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let high = authority.dyn_clearance(Level::High);
+    /// let secret: Sec<High, &str> = Sec::new("hunter2");
+    /// let mut public = LioSink::new(Level::Low, Vec::new());
+    ///
+    /// let result = Lio::new(Level::Low, &high).unwrap().run(|lio| {
+    ///     let password = lio.unlabel_sec(secret)?;
+    ///     lio.write(&mut public, password.as_bytes())
+    /// });
+    ///
+    /// assert!(matches!(result.reveal(&high).unwrap(), Err(LioError::BelowCurrent { .. })));
+    /// assert!(public.into_inner(&authority.dyn_clearance(Level::Low)).unwrap().is_empty());
+    /// ```
+    pub fn write<W>(&mut self, sink: &mut LioSink<L, W>, data: &[u8]) -> Result<(), LioError<L>>
+    where
+        W: Write,
+    {
+        self.guard(&sink.label)?;
+        sink.writer.write_all(data)?;
+        Ok(())
+    }
+
+    /// Runs `f` in a copy of this context, and labels its outcome with `label`.
+    /// The current label is left as it was, so `f` can look at data above it without tainting the rest of the computation.
+    ///
+    /// `label` must be between the current label and the clearance, which is the only way this can fail.
+    /// `f` can't raise its own current label above `label`, and any error it runs into is labeled along with its result,
+    /// as whether it failed may depend on what it looked at.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::dynamic::Level;
+    /// use seclib::lio::Lio;
+    ///
+    /// let high = Authority::acquire().unwrap().dyn_clearance(Level::High);
+    /// let secret: Sec<High, u32> = Sec::new(0);
+    ///
+    /// let mut lio = Lio::new(Level::Low, &high).unwrap();
+    /// let quotient = lio.to_labeled(Level::High, |inner| {
+    ///     let divisor = inner.unlabel_sec(secret)?;
+    ///     if divisor == 0 {
+    ///         // fails, as the secret has been looked at, but only if it's 0
+    ///         inner.label(Level::Low, "division by zero")?;
+    ///     }
+    ///     Ok(100 / divisor)
+    /// }).unwrap();
+    ///
+    /// assert_eq!(lio.current(), &Level::Low);
+    /// assert!(quotient.reveal(&high).unwrap().is_err());
+    /// ```
+    pub fn to_labeled<A, F>(&mut self, label: L, f: F) -> Result<Labeled<A, L>, LioError<L>>
+    where
+        F: FnOnce(&mut Lio<L>) -> Result<A, LioError<L>>,
+    {
+        self.guard(&label)?;
+
+        let mut inner = Lio { current: self.current.clone(), clearance: label.clone() };
+        let outcome = f(&mut inner);
+
+        Ok(DynSec::new(label, outcome))
+    }
+}
@@ -0,0 +1,266 @@
+//! Locked, guarded memory for long-lived secrets like signing keys. Linux on x86, x86-64, ARM and AArch64 only,
+//! where the flags below have the values they're given here; on other architectures, like MIPS or SPARC, some differ.
+//!
+//! A `Locked<T>` keeps its value on pages of its own, which are
+//!
+//! * locked into RAM with `mlock`, so they're never swapped out to disk,
+//! * left out of core dumps,
+//! * surrounded by inaccessible guard pages, so running off either end of the value faults instead of reading a neighbour,
+//! * and inaccessible themselves (`PROT_NONE`) while nobody is using the value.
+//!
+//! The value is only reachable within `with` and `with_mut`, which open the pages up for the duration of a closure.
+//! When dropped, it's overwritten with zeros before the pages are unlocked and unmapped.
+//!
+//! As the value is copied bitwise onto the locked pages, it has to be `Copy`: plain data like `[u8; 32]`,
+//! rather than something like a `Vec` whose contents would live on the regular heap.
+//! Locked memory is limited by `RLIMIT_MEMLOCK`, so this is meant for a few small secrets, not bulk data.
+
+use std::cell::Cell;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::os::raw::{c_int, c_long, c_void};
+use std::ptr;
+use std::sync::atomic::{self, Ordering};
+
+use super::Sec;
+use super::security_level as sl;
+
+// From the kernel's asm-generic headers, and glibc's and musl's unistd.h
+const PROT_NONE: c_int = 0;
+const PROT_READ: c_int = 1;
+const PROT_WRITE: c_int = 2;
+const MAP_PRIVATE: c_int = 0x02;
+const MAP_ANONYMOUS: c_int = 0x20;
+const MADV_DONTDUMP: c_int = 16;
+const SC_PAGESIZE: c_int = 30;
+
+extern "C" {
+    fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: c_long) -> *mut c_void;
+    fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    fn mprotect(addr: *mut c_void, len: usize, prot: c_int) -> c_int;
+    fn mlock(addr: *const c_void, len: usize) -> c_int;
+    fn munlock(addr: *const c_void, len: usize) -> c_int;
+    fn madvise(addr: *mut c_void, len: usize, advice: c_int) -> c_int;
+    fn sysconf(name: c_int) -> c_long;
+}
+
+// Turns a libc-style return code into an `io::Result`
+fn check(result: c_int) -> io::Result<()> {
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// A value kept on locked pages between guard pages, inaccessible while not in use.
+///
+/// # Example
+/// ```
+/// use seclib::prelude::*;
+/// use seclib::locked::Locked;
+///
+/// let high = Authority::acquire().unwrap().clearance::<High>();
+///
+/// let key: Sec<High, Locked<[u8; 32]>> = Sec::new_locked([7; 32]).unwrap();
+/// let checksum = key.with(|key| key.iter().map(|&b| b as u32).sum::<u32>());
+///
+/// assert_eq!(checksum.reveal(&high), 224);
+/// ```
+pub struct Locked<T>
+where
+    T: Copy,
+{
+    // the whole mapping, guard pages included
+    region: *mut u8,
+    region_len: usize,
+    // the pages holding the value, and the value itself
+    pages: *mut u8,
+    pages_len: usize,
+    data: *mut T,
+    // how many `with`s are currently running
+    readers: Cell<usize>,
+}
+
+// SAFETY: the value is only reachable through the `Locked`, like with a `Box`, so it can move between threads with it.
+// It isn't `Sync`, as concurrent `with`s would protect the pages from under each other.
+unsafe impl<T> Send for Locked<T> where T: Copy + Send {}
+
+impl<T> Locked<T>
+where
+    T: Copy,
+{
+    /// Constructor. Copies `data` onto freshly mapped and locked pages.
+    ///
+    /// Fails when the pages can't be mapped or locked, e.g. as `RLIMIT_MEMLOCK` is exhausted.
+    pub fn new(data: T) -> io::Result<Self> {
+        // SAFETY: `sysconf` only reads its argument.
+        let page = unsafe { sysconf(SC_PAGESIZE) } as usize;
+        let size = mem::size_of::<T>();
+        let pages_len = size.div_ceil(page).max(1) * page;
+        let region_len = pages_len + 2 * page;
+
+        // SAFETY: a fresh anonymous mapping at an address of the kernel's choosing can't alias anything.
+        let region = unsafe {
+            mmap(ptr::null_mut(), region_len, PROT_NONE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0)
+        };
+        if region as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let region = region as *mut u8;
+        // SAFETY: the region is `region_len` bytes long, which is more than one page.
+        let pages = unsafe { region.add(page) };
+        // the value goes at the very end of its pages, so an overflow runs straight into the guard page
+        let offset = (pages_len - size) & !(mem::align_of::<T>() - 1);
+
+        let locked = Locked {
+            region,
+            region_len,
+            pages,
+            pages_len,
+            // SAFETY: `offset` is at most `pages_len - size`, so the value fits within the pages.
+            data: unsafe { pages.add(offset) } as *mut T,
+            readers: Cell::new(0),
+        };
+
+        locked.protect(PROT_READ | PROT_WRITE)?;
+        // SAFETY: the pages are mapped and were just made writable, and `data` is aligned for `T` within them.
+        unsafe {
+            check(madvise(pages as *mut c_void, pages_len, MADV_DONTDUMP))?;
+            check(mlock(pages as *const c_void, pages_len))?;
+            ptr::write(locked.data, data);
+        }
+        locked.protect(PROT_NONE)?;
+
+        Ok(locked)
+    }
+
+    fn protect(&self, prot: c_int) -> io::Result<()> {
+        // SAFETY: the pages are mapped for as long as `self` lives, and `prot` only changes who may access them.
+        check(unsafe { mprotect(self.pages as *mut c_void, self.pages_len, prot) })
+    }
+
+    /// Gives `f` read access to the value, with the pages readable only for as long as it runs.
+    pub fn with<B, F>(&self, f: F) -> B
+    where
+        F: FnOnce(&T) -> B,
+    {
+        if self.readers.get() == 0 {
+            self.protect(PROT_READ).expect("failed to unprotect locked pages");
+        }
+        self.readers.set(self.readers.get() + 1);
+        let _guard = Reprotect { locked: self };
+
+        // SAFETY: the pages are readable until the guard is dropped, and the value was initialized by `new`.
+        // Only shared references exist while readers are counted, as `with_mut` takes `&mut self`.
+        f(unsafe { &*self.data })
+    }
+
+    /// Gives `f` write access to the value, with the pages writable only for as long as it runs.
+    pub fn with_mut<B, F>(&mut self, f: F) -> B
+    where
+        F: FnOnce(&mut T) -> B,
+    {
+        self.protect(PROT_READ | PROT_WRITE).expect("failed to unprotect locked pages");
+        self.readers.set(1);
+        let data = self.data;
+        let _guard = Reprotect { locked: self };
+
+        // SAFETY: the pages are writable until the guard is dropped, and `&mut self` makes this the only reference.
+        f(unsafe { &mut *data })
+    }
+}
+
+// Makes the pages inaccessible again once the last access is over, even if it panicked
+struct Reprotect<'a, T>
+where
+    T: Copy + 'a,
+{
+    locked: &'a Locked<T>,
+}
+
+impl<'a, T> Drop for Reprotect<'a, T>
+where
+    T: Copy,
+{
+    fn drop(&mut self) {
+        let readers = self.locked.readers.get() - 1;
+        self.locked.readers.set(readers);
+        if readers == 0 {
+            self.locked.protect(PROT_NONE).expect("failed to protect locked pages");
+        }
+    }
+}
+
+impl<T> Drop for Locked<T>
+where
+    T: Copy,
+{
+    fn drop(&mut self) {
+        // SAFETY: the pages are only written once made writable, and nothing refers to them after the unmapping,
+        // as `self` is going away. `T: Copy`, so there's no destructor to run first.
+        unsafe {
+            if self.protect(PROT_READ | PROT_WRITE).is_ok() {
+                for i in 0..self.pages_len {
+                    ptr::write_volatile(self.pages.add(i), 0);
+                }
+                atomic::compiler_fence(Ordering::SeqCst);
+            }
+            munlock(self.pages as *const c_void, self.pages_len);
+            munmap(self.region as *mut c_void, self.region_len);
+        }
+    }
+}
+
+impl<T> fmt::Debug for Locked<T>
+where
+    T: Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Locked({} bytes)", mem::size_of::<T>())
+    }
+}
+
+impl<S, T> Sec<S, Locked<T>>
+where
+    S: sl::SecurityLevel,
+    T: Copy,
+{
+    /// Constructor. Keeps `data` in locked, guarded memory, see `Locked`.
+    pub fn new_locked(data: T) -> io::Result<Self> {
+        Locked::new(data).map(Sec::new)
+    }
+
+    /// Like `map`, but borrows the value, keeping it locked away.
+    pub fn with<B, F>(&self, f: F) -> Sec<S, B>
+    where
+        F: FnOnce(&T) -> B,
+    {
+        Sec::new(self.data.with(f))
+    }
+
+    /// Like `with`, but with write access to the value.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::locked::Locked;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let mut counter: Sec<High, Locked<u64>> = Sec::new_locked(0).unwrap();
+    /// counter.with_mut(|c| *c += 1);
+    /// let previous = counter.with_mut(|c| { *c += 1; *c - 1 });
+    ///
+    /// assert_eq!(previous.reveal(&high), 1);
+    /// assert_eq!(counter.with(|c| *c).reveal(&high), 2);
+    /// ```
+    pub fn with_mut<B, F>(&mut self, f: F) -> Sec<S, B>
+    where
+        F: FnOnce(&mut T) -> B,
+    {
+        Sec::new(self.data.with_mut(f))
+    }
+}
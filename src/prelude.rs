@@ -1,3 +1,9 @@
 //! Provides all the essential types à la carte ready to use.
 pub use super::Sec;
-pub use super::security_level::{SecurityLevel, High, Low};
\ No newline at end of file
+pub use super::security_level::{SecurityLevel, Join, Meet, JoinOf, MeetOf, High, Low};
+pub use super::clearance::{Authority, Clearance, DynClearance};
+pub use super::sec_io::{SecIO, Sink};
+pub use super::declassify::Declassifier;
+pub use super::dynamic::{DynSec, Label, StaticLabel};
+pub use super::integrity::{IntegrityLevel, IntegrityJoin, IntegrityMeet, Int, Trusted, Untrusted};
+pub use super::product::Pair;
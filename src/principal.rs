@@ -0,0 +1,130 @@
+//! Principals, and the acts-for relation between them.
+//!
+//! A principal is anyone who can own or read data: a user, a group, a role, or a service.
+//! When `alice` *acts for* `bob`, `alice` has all of `bob`'s privileges, e.g. a manager acting for their team.
+//! The relation is reflexive and transitive, and is kept in a `PrincipalHierarchy`.
+//!
+//! Anyone can name a principal, but acting as one takes a `Privilege`, which like a `Clearance`
+//! can only be minted by the process' `Authority`. Reading as a principal, changing their policies,
+//! and letting somebody else act for them all ask for it.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A named principal.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Principal(String);
+
+impl Principal {
+    /// Constructor.
+    pub fn new(name: impl Into<String>) -> Self {
+        Principal(name.into())
+    }
+
+    /// The principal's name.
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The capability to act as a principal.
+///
+/// It can't be made out of thin air: the only source of privileges is `Authority::privilege`.
+/// Like clearances, privileges can't be cloned, so handing one out is always explicit.
+pub struct Privilege {
+    principal: Principal,
+}
+
+impl Privilege {
+    pub(crate) fn mint(principal: Principal) -> Self {
+        Privilege { principal }
+    }
+
+    /// The principal this privilege lets its holder act as.
+    pub fn principal(&self) -> &Principal {
+        &self.principal
+    }
+}
+
+impl fmt::Debug for Privilege {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Privilege({})", self.principal)
+    }
+}
+
+/// The acts-for relation between principals.
+///
+/// # Example
+/// ```
+/// use seclib::prelude::*;
+/// use seclib::principal::{Principal, PrincipalHierarchy};
+///
+/// let authority = Authority::acquire().unwrap();
+///
+/// let alice = Principal::new("alice");
+/// let bob = authority.privilege(&Principal::new("bob"));
+/// let carol = authority.privilege(&Principal::new("carol"));
+///
+/// let mut hierarchy = PrincipalHierarchy::new();
+/// hierarchy.add_acts_for(&alice, &bob).add_acts_for(bob.principal(), &carol);
+///
+/// let (bob, carol) = (bob.principal(), carol.principal());
+///
+/// assert!(hierarchy.acts_for(&alice, carol));
+/// assert!(!hierarchy.acts_for(carol, &alice));
+/// assert!(hierarchy.acts_for(bob, carol));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PrincipalHierarchy {
+    // direct edges only, the transitive closure is worked out when asked
+    acts_for: HashMap<Principal, HashSet<Principal>>,
+}
+
+impl PrincipalHierarchy {
+    /// Constructor. Every principal only acts for itself.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lets `superior` act for `subordinate`, and by extension for everyone `subordinate` acts for.
+    /// That hands over all of `subordinate`'s privileges, so it takes `subordinate`'s `Privilege`.
+    pub fn add_acts_for(&mut self, superior: &Principal, subordinate: &Privilege) -> &mut Self {
+        self.acts_for.entry(superior.clone()).or_default().insert(subordinate.principal.clone());
+        self
+    }
+
+    /// Revokes a relation added with `add_acts_for`. Relations that follow from other ones are left alone.
+    ///
+    /// As revoking only ever takes privileges away, it needs no `Privilege`.
+    pub fn remove_acts_for(&mut self, superior: &Principal, subordinate: &Principal) -> &mut Self {
+        if let Some(subordinates) = self.acts_for.get_mut(superior) {
+            subordinates.remove(subordinate);
+        }
+        self
+    }
+
+    /// Whether `superior` acts for `subordinate`, directly, transitively, or by being the same principal.
+    pub fn acts_for(&self, superior: &Principal, subordinate: &Principal) -> bool {
+        let mut seen = HashSet::new();
+        let mut pending = vec![superior];
+
+        while let Some(principal) = pending.pop() {
+            if principal == subordinate {
+                return true;
+            }
+            if seen.insert(principal) {
+                if let Some(subordinates) = self.acts_for.get(principal) {
+                    pending.extend(subordinates);
+                }
+            }
+        }
+
+        false
+    }
+}
@@ -0,0 +1,75 @@
+//! Product labels, tracking confidentiality and integrity at the same time.
+//!
+//! `Pair<C, I>` pairs a `SecurityLevel` with an `IntegrityLevel`, and is itself a `SecurityLevel`.
+//! It's ordered component-wise: `Pair<C1, I1>` &leq; `Pair<C2, I2>` exactly when `C1` &leq; `C2`
+//! and data at `I1` may flow to `I2`. So a `Sec<Pair<High, Trusted>, A>` may be lifted to `Pair<High, Untrusted>`,
+//! but never to `Pair<Low, Trusted>`, and a `Sec<Pair<High, Untrusted>, A>` never to `Pair<High, Trusted>`.
+//!
+//! Every impl is derived from the components, so any level from `security_lattice!` works too.
+
+use super::integrity::{IntegrityJoin, IntegrityLevel, IntegrityMeet};
+use super::security_level::{Join, Meet, SecurityLevel};
+use super::security_level::sealed::Sealed;
+
+/// A confidentiality level `C` paired with an integrity level `I`.
+///
+/// # Examples
+/// ```
+/// use seclib::prelude::*;
+///
+/// let data: Sec<Pair<Low, Trusted>, i32> = Sec::new(42);
+/// let result: Sec<Pair<High, Untrusted>, i32> = data.lift(Pair(High, Untrusted));
+///
+/// let clearance = Authority::acquire().unwrap().clearance::<Pair<High, Untrusted>>();
+/// assert_eq!(result.reveal(&clearance), 42);
+/// ```
+/// Untrusted data can't be passed off as trusted, even when it gets more secret:
+/// ```compile_fail
+/// use seclib::prelude::*;
+///
+/// let data: Sec<Pair<Low, Untrusted>, i32> = Sec::new(42);
+/// let result = data.lift(Pair(High, Trusted)); // ERROR: the trait `IntegrityLevel<Untrusted>` is not implemented for `Trusted`
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pair<C, I>(pub C, pub I);
+
+impl<C1, I1, C2, I2> Sealed<Pair<C1, I1>> for Pair<C2, I2>
+where
+    C1: SecurityLevel,
+    I1: IntegrityLevel,
+    C2: SecurityLevel<C1>,
+    I2: IntegrityLevel<I1>,
+{
+}
+
+/// Implements (C1, I1) &leq; (C2, I2) whenever C1 &leq; C2 and I1 &#8849; I2
+impl<C1, I1, C2, I2> SecurityLevel<Pair<C1, I1>> for Pair<C2, I2>
+where
+    C1: SecurityLevel,
+    I1: IntegrityLevel,
+    C2: SecurityLevel<C1>,
+    I2: IntegrityLevel<I1>,
+{
+}
+
+/// Implements (C1, I1) &vee; (C2, I2) = (C1 &vee; C2, I1 &#8852; I2)
+impl<C1, I1, C2, I2> Join<Pair<C2, I2>> for Pair<C1, I1>
+where
+    C1: Join<C2>,
+    I1: IntegrityJoin<I2>,
+    C2: SecurityLevel,
+    I2: IntegrityLevel,
+{
+    type Output = Pair<C1::Output, I1::Output>;
+}
+
+/// Implements (C1, I1) &wedge; (C2, I2) = (C1 &wedge; C2, I1 &#8851; I2)
+impl<C1, I1, C2, I2> Meet<Pair<C2, I2>> for Pair<C1, I1>
+where
+    C1: Meet<C2>,
+    I1: IntegrityMeet<I2>,
+    C2: SecurityLevel,
+    I2: IntegrityLevel,
+{
+    type Output = Pair<C1::Output, I1::Output>;
+}
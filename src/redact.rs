@@ -0,0 +1,115 @@
+//! Redacted formatting for `Sec`.
+//!
+//! A `Sec` never prints the data it wraps. Both `Debug` and `Display` render as `Sec<Level>(<redacted>)`,
+//! so a stray `println!("{:?}", secret)` or a `Sec` nested deep inside some logged struct can't leak anything.
+//!
+//! Formatting the payload is opt-in through `Sec::unredacted`, which asks for the same clearance as `reveal`.
+
+use std::any;
+use std::fmt;
+
+use super::Sec;
+use super::clearance::Clearance;
+use super::security_level as sl;
+
+/// Returns the name of a type without any module paths,
+/// so `seclib::security_level::High` becomes `High`.
+pub(crate) fn short_type_name<T: ?Sized>() -> String {
+    let full = any::type_name::<T>();
+    let mut name = String::with_capacity(full.len());
+    // index into `name` where the current path segment began
+    let mut segment_start = 0;
+    let mut chars = full.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            // drop the module path we just passed
+            name.truncate(segment_start);
+        } else {
+            name.push(c);
+            if !(c.is_alphanumeric() || c == '_') {
+                segment_start = name.len();
+            }
+        }
+    }
+
+    name
+}
+
+impl<S, A> fmt::Debug for Sec<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sec<{}>(<redacted>)", short_type_name::<S>())
+    }
+}
+
+impl<S, A> fmt::Display for Sec<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sec<{}>(<redacted>)", short_type_name::<S>())
+    }
+}
+
+/// A borrowed view of a `Sec`'s data that formats it in the clear.
+///
+/// Created by `Sec::unredacted`. It implements `Debug` and `Display` whenever the wrapped type does.
+pub struct Unredacted<'a, A: 'a> {
+    data: &'a A,
+}
+
+impl<'a, A> fmt::Debug for Unredacted<'a, A>
+where
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.data, f)
+    }
+}
+
+impl<'a, A> fmt::Display for Unredacted<'a, A>
+where
+    A: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.data, f)
+    }
+}
+
+impl<S, A> Sec<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    /// Gives formatting access to the data within a `Sec`, without moving it.
+    /// Like `reveal`, it must be supplied with a `Clearance` for a security level &geq; the `Sec`'s.
+    ///
+    /// # Examples
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let data: Sec<High, &str> = Sec::new("Attack at Dawn!");
+    ///
+    /// assert_eq!(format!("{:?}", data), "Sec<High>(<redacted>)");
+    /// assert_eq!(format!("{}", data.unredacted(&authority.clearance::<High>())), "Attack at Dawn!");
+    /// ```
+    /// Asking with too low a clearance won't compile:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let data: Sec<High, &str> = Sec::new("Attack at Dawn!");
+    ///
+    /// println!("{}", data.unredacted(&authority.clearance::<Low>())); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// ```
+    pub fn unredacted<'a, S2>(&'a self, _: &Clearance<S2>) -> Unredacted<'a, A>
+    where
+        S2: sl::SecurityLevel<S> + sl::SecurityLevel,
+    {
+        Unredacted { data: &self.data }
+    }
+}
@@ -0,0 +1,272 @@
+//! The SecIO monad, which wraps side-effecting computations with a `SecurityLevel`.
+//!
+//! A `SecIO<S, A>` is a computation that runs at level `S`:
+//! it may look at any data of a level &leq; `S`, but it may only write to sinks of a level &geq; `S`.
+//! That way, nothing it learns can end up somewhere less secret than where it came from.
+//! Running it gives back its result wrapped in a `Sec<S, A>`.
+//!
+//! Nothing happens until `run` is called; building a `SecIO` only describes the computation.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use super::Sec;
+use super::clearance::Clearance;
+use super::redact::short_type_name;
+use super::security_level as sl;
+
+/// The SecIO monad, mirroring `SecIO` from Haskell's SecLib.
+pub struct SecIO<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    security_level: PhantomData<S>,
+    action: Box<dyn FnOnce() -> A>,
+}
+
+impl<S, A> SecIO<S, A>
+where
+    S: sl::SecurityLevel,
+    A: 'static,
+{
+    /// Constructor. Creates a computation which does nothing but return `data`.
+    pub fn new(data: A) -> Self {
+        SecIO::from_fn(move || data)
+    }
+
+    pub(crate) fn from_fn<F>(f: F) -> Self
+    where
+        F: FnOnce() -> A + 'static,
+    {
+        SecIO { security_level: PhantomData, action: Box::new(f) }
+    }
+
+    /// Reads the data from within a `Sec` of a level &leq; the computation's.
+    ///
+    /// # Examples
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let data: Sec<Low, i32> = Sec::new(12);
+    /// let result: SecIO<High, i32> = SecIO::value(data).map(|i| i * 2);
+    ///
+    /// assert_eq!(result.run().reveal(&high), 24);
+    /// ```
+    /// A low computation can't read high data:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let data: Sec<High, i32> = Sec::new(12);
+    /// let result: SecIO<Low, i32> = SecIO::value(data); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// ```
+    pub fn value<S2>(sec: Sec<S2, A>) -> Self
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+    {
+        SecIO::new(sec.data)
+    }
+
+    /// Maps a function over the result of a `SecIO`, returning a new `SecIO` with the same security level.
+    pub fn map<B, F>(self, f: F) -> SecIO<S, B>
+    where
+        B: 'static,
+        F: FnOnce(A) -> B + 'static,
+    {
+        let SecIO { action, .. } = self;
+        SecIO::from_fn(move || f(action()))
+    }
+
+    /// Sequences two `SecIO`s of the same security level, feeding the result of the first into `f`.
+    ///
+    /// `and_then` represents monadic bind, just like `Sec::and_then`.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let secret: Sec<High, &str> = Sec::new("hunter2");
+    /// let sink: Sink<High, Vec<u8>> = Sink::new(Vec::new());
+    /// let log = sink.clone();
+    ///
+    /// let program: SecIO<High, ()> = SecIO::value(secret)
+    ///     .and_then(move |s| SecIO::write(&log, s))
+    ///     .map(|result| result.expect("writing to a Vec can't fail"));
+    ///
+    /// program.run();
+    ///
+    /// assert_eq!(sink.into_inner(&high).unwrap(), b"hunter2");
+    /// ```
+    pub fn and_then<B, F>(self, f: F) -> SecIO<S, B>
+    where
+        B: 'static,
+        F: FnOnce(A) -> SecIO<S, B> + 'static,
+    {
+        let SecIO { action, .. } = self;
+        SecIO::from_fn(move || (f(action()).action)())
+    }
+
+    /// Embeds a computation of a higher security level into a lower one.
+    /// Its result stays at the higher level, wrapped in a `Sec`.
+    ///
+    /// # Examples
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let high = authority.clearance::<High>();
+    /// let low = authority.clearance::<Low>();
+    ///
+    /// let secret: SecIO<High, i32> = SecIO::new(42);
+    /// let program: SecIO<Low, Sec<High, i32>> = secret.plug(Low);
+    ///
+    /// assert_eq!(program.run().reveal(&low).reveal(&high), 42);
+    /// ```
+    /// Plugging a computation into a higher one would let it write down, and so isn't allowed:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let public: SecIO<Low, i32> = SecIO::new(42);
+    /// let program = public.plug(High); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// ```
+    pub fn plug<S2>(self, _: S2) -> SecIO<S2, Sec<S, A>>
+    where
+        S: sl::SecurityLevel<S2> + 'static,
+        S2: sl::SecurityLevel,
+    {
+        let SecIO { action, .. } = self;
+        SecIO::from_fn(move || Sec::new(action()))
+    }
+
+    /// Runs the computation, carrying out all its effects, and returns the result wrapped in a `Sec`.
+    pub fn run(self) -> Sec<S, A> {
+        Sec::new((self.action)())
+    }
+}
+
+impl<S> SecIO<S, io::Result<()>>
+where
+    S: sl::SecurityLevel,
+{
+    /// Writes `data` to a sink of a level &geq; the computation's.
+    ///
+    /// # Examples
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    ///
+    /// let sink: Sink<High, Vec<u8>> = Sink::new(Vec::new());
+    /// let program: SecIO<Low, _> = SecIO::write(&sink, "public knowledge");
+    ///
+    /// program.run().reveal(&authority.clearance::<Low>()).unwrap();
+    /// assert_eq!(sink.into_inner(&authority.clearance::<High>()).unwrap(), b"public knowledge");
+    /// ```
+    /// Writing down is an error:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let sink: Sink<Low, Vec<u8>> = Sink::new(Vec::new());
+    /// let program: SecIO<High, _> = SecIO::write(&sink, "secret"); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// ```
+    pub fn write<S2, W, D>(sink: &Sink<S2, W>, data: D) -> Self
+    where
+        S2: sl::SecurityLevel<S> + sl::SecurityLevel,
+        W: Write + 'static,
+        D: AsRef<[u8]> + 'static,
+    {
+        let writer = sink.writer.clone();
+        SecIO::from_fn(move || writer.borrow_mut().write_all(data.as_ref()))
+    }
+}
+
+impl<S, A> fmt::Debug for SecIO<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecIO<{}>(..)", short_type_name::<S>())
+    }
+}
+
+/// Anything that can be written to, labeled with a `SecurityLevel`.
+///
+/// Clones share the same underlying writer.
+pub struct Sink<S, W>
+where
+    S: sl::SecurityLevel,
+{
+    security_level: PhantomData<S>,
+    writer: Rc<RefCell<W>>,
+}
+
+impl<S, W> Sink<S, W>
+where
+    S: sl::SecurityLevel,
+    W: Write,
+{
+    /// Constructor. Labels `writer` with the security level `S`.
+    pub fn new(writer: W) -> Self {
+        Sink { security_level: PhantomData, writer: Rc::new(RefCell::new(writer)) }
+    }
+
+    /// Gives back the underlying writer, provided this is the only handle to it left.
+    /// As everything written to it is at the sink's level, it must be supplied with a `Clearance` for a security level &geq; the sink's,
+    /// like `Sec::reveal`.
+    ///
+    /// # Examples
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let sink: Sink<High, Vec<u8>> = Sink::new(Vec::new());
+    /// SecIO::<High, _>::write(&sink, "top secret").run();
+    ///
+    /// assert_eq!(sink.into_inner(&high).unwrap(), b"top secret");
+    /// ```
+    /// Asking with too low a clearance won't compile:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let sink: Sink<High, Vec<u8>> = Sink::new(Vec::new());
+    ///
+    /// let written = sink.into_inner(&authority.clearance::<Low>()); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// ```
+    pub fn into_inner<S2>(self, _: &Clearance<S2>) -> Result<W, Self>
+    where
+        S2: sl::SecurityLevel<S> + sl::SecurityLevel,
+    {
+        let Sink { security_level, writer } = self;
+        match Rc::try_unwrap(writer) {
+            Ok(cell) => Ok(cell.into_inner()),
+            Err(writer) => Err(Sink { security_level, writer }),
+        }
+    }
+}
+
+impl<S, W> Clone for Sink<S, W>
+where
+    S: sl::SecurityLevel,
+{
+    fn clone(&self) -> Self {
+        Sink { security_level: PhantomData, writer: self.writer.clone() }
+    }
+}
+
+impl<S, W> fmt::Debug for Sink<S, W>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sink<{}>(..)", short_type_name::<S>())
+    }
+}
@@ -0,0 +1,264 @@
+//! Labeled mutable references, mirroring `Ref` from Haskell's SecLib.
+//!
+//! A `SecRef<S, A>` is a shared, mutable cell holding data of security level `S`.
+//! Reading it gives a `Sec<S, A>`, so the contents stay labeled,
+//! and it only accepts writes of data at a level &leq; `S`, so nothing more secret can be stored in it.
+//!
+//! Clones share the same cell. `SecRef` is single-threaded, built on `Rc<RefCell<_>>`;
+//! `SyncSecRef` is its thread-safe counterpart, built on `Arc<RwLock<_>>`.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use super::Sec;
+use super::redact::short_type_name;
+use super::security_level as sl;
+
+/// A shared, mutable cell labeled with a `SecurityLevel`.
+pub struct SecRef<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    security_level: PhantomData<S>,
+    cell: Rc<RefCell<A>>,
+}
+
+impl<S, A> SecRef<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    /// Constructor. Labels `data` with the security level `S`.
+    pub fn new(data: A) -> Self {
+        SecRef { security_level: PhantomData, cell: Rc::new(RefCell::new(data)) }
+    }
+
+    /// Constructor. Stores the data of a `Sec` of a level &leq; `S`.
+    pub fn from_sec<S2>(data: Sec<S2, A>) -> Self
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+    {
+        SecRef::new(data.data)
+    }
+
+    /// Reads a copy of the contents, at the reference's security level.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::sec_ref::SecRef;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let cell: SecRef<High, i32> = SecRef::new(1);
+    /// let alias = cell.clone();
+    /// alias.write(Sec::<Low, i32>::new(2));
+    ///
+    /// assert_eq!(cell.read().reveal(&high), 2);
+    /// ```
+    /// The contents can't be read into anything less secret:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    /// use seclib::sec_ref::SecRef;
+    ///
+    /// let cell: SecRef<High, i32> = SecRef::new(1);
+    /// let data: Sec<Low, i32> = cell.read(); // ERROR: expected `Low`, found `High`
+    /// ```
+    pub fn read(&self) -> Sec<S, A>
+    where
+        A: Clone,
+    {
+        self.read_with(A::clone)
+    }
+
+    /// Applies `f` to the contents without copying them, keeping the result at the reference's security level.
+    pub fn read_with<B, F>(&self, f: F) -> Sec<S, B>
+    where
+        F: FnOnce(&A) -> B,
+    {
+        Sec::new(f(&self.cell.borrow()))
+    }
+
+    /// Overwrites the contents with the data of a `Sec` of a level &leq; `S`.
+    ///
+    /// # Examples
+    /// ```
+    /// use seclib::prelude::*;
+    /// use seclib::sec_ref::SecRef;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let cell: SecRef<High, &str> = SecRef::new("");
+    /// cell.write(Sec::<Low, &str>::new("public"));
+    /// cell.write(Sec::<High, &str>::new("secret"));
+    ///
+    /// assert_eq!(cell.read().reveal(&high), "secret");
+    /// ```
+    /// Secrets can't be written to a public reference:
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    /// use seclib::sec_ref::SecRef;
+    ///
+    /// let cell: SecRef<Low, &str> = SecRef::new("");
+    /// cell.write(Sec::<High, &str>::new("secret")); // ERROR: expected `Sec<Low, &str>`, found `Sec<High, &str>`
+    /// ```
+    pub fn write<S2>(&self, data: Sec<S2, A>)
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+    {
+        *self.cell.borrow_mut() = data.data;
+    }
+
+    /// Updates the contents in place with the data of a `Sec` of a level &leq; `S`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use seclib::prelude::*;
+    /// use seclib::sec_ref::SecRef;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let sessions: SecRef<High, HashMap<u32, String>> = SecRef::new(HashMap::new());
+    /// sessions.update(Sec::<High, _>::new((7, "token".to_string())), |map, (id, token)| {
+    ///     map.insert(id, token);
+    /// });
+    ///
+    /// assert_eq!(sessions.read_with(|map| map.len()).reveal(&high), 1);
+    /// ```
+    pub fn update<S2, B, F>(&self, data: Sec<S2, B>, f: F)
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+        F: FnOnce(&mut A, B),
+    {
+        f(&mut self.cell.borrow_mut(), data.data);
+    }
+}
+
+impl<S, A> Clone for SecRef<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn clone(&self) -> Self {
+        SecRef { security_level: PhantomData, cell: self.cell.clone() }
+    }
+}
+
+impl<S, A> fmt::Debug for SecRef<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecRef<{}>(<redacted>)", short_type_name::<S>())
+    }
+}
+
+/// The thread-safe counterpart of `SecRef`, which can be shared between threads.
+///
+/// Readers share the lock, writers take it exclusively. A panic while holding the lock doesn't poison it:
+/// later readers and writers see the contents as the panicking thread left them.
+///
+/// # Example
+/// ```
+/// use std::thread;
+/// use seclib::prelude::*;
+/// use seclib::sec_ref::SyncSecRef;
+///
+/// let high = Authority::acquire().unwrap().clearance::<High>();
+///
+/// let total: SyncSecRef<High, u32> = SyncSecRef::new(0);
+/// let handles: Vec<_> = (1..=4).map(|i| {
+///     let total = total.clone();
+///     thread::spawn(move || total.update(Sec::<Low, u32>::new(i), |sum, i| *sum += i))
+/// }).collect();
+///
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+/// assert_eq!(total.read().reveal(&high), 10);
+/// ```
+pub struct SyncSecRef<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    security_level: PhantomData<S>,
+    lock: Arc<RwLock<A>>,
+}
+
+impl<S, A> SyncSecRef<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    /// Constructor. Labels `data` with the security level `S`.
+    pub fn new(data: A) -> Self {
+        SyncSecRef { security_level: PhantomData, lock: Arc::new(RwLock::new(data)) }
+    }
+
+    /// Constructor. Stores the data of a `Sec` of a level &leq; `S`.
+    pub fn from_sec<S2>(data: Sec<S2, A>) -> Self
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+    {
+        SyncSecRef::new(data.data)
+    }
+
+    /// Reads a copy of the contents, at the reference's security level.
+    pub fn read(&self) -> Sec<S, A>
+    where
+        A: Clone,
+    {
+        self.read_with(A::clone)
+    }
+
+    /// Applies `f` to the contents without copying them, keeping the result at the reference's security level.
+    pub fn read_with<B, F>(&self, f: F) -> Sec<S, B>
+    where
+        F: FnOnce(&A) -> B,
+    {
+        let guard = self.lock.read().unwrap_or_else(PoisonError::into_inner);
+        Sec::new(f(&guard))
+    }
+
+    /// Overwrites the contents with the data of a `Sec` of a level &leq; `S`.
+    pub fn write<S2>(&self, data: Sec<S2, A>)
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+    {
+        *self.lock.write().unwrap_or_else(PoisonError::into_inner) = data.data;
+    }
+
+    /// Updates the contents in place with the data of a `Sec` of a level &leq; `S`.
+    pub fn update<S2, B, F>(&self, data: Sec<S2, B>, f: F)
+    where
+        S: sl::SecurityLevel<S2>,
+        S2: sl::SecurityLevel,
+        F: FnOnce(&mut A, B),
+    {
+        f(&mut self.lock.write().unwrap_or_else(PoisonError::into_inner), data.data);
+    }
+}
+
+impl<S, A> Clone for SyncSecRef<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn clone(&self) -> Self {
+        SyncSecRef { security_level: PhantomData, lock: self.lock.clone() }
+    }
+}
+
+impl<S, A> fmt::Debug for SyncSecRef<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SyncSecRef<{}>(<redacted>)", short_type_name::<S>())
+    }
+}
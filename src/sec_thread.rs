@@ -0,0 +1,155 @@
+//! Labeled threads, in the style of `lFork` and `lWait` from Haskell's LIO.
+//!
+//! A thread spawned at level `S` computes a `Sec<S, A>`, and joining it gives back a `Sec<S, Result<A, SecPanic>>`.
+//! A panic in the thread is caught and labeled along with the result, so whether a secret computation failed
+//! is just as secret as what it would have returned.
+//!
+//! The panic message is still printed by the panic hook as usual; replace it with `std::panic::set_hook`
+//! if standard error is visible to observers that shouldn't learn about the failure.
+
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+use super::Sec;
+use super::redact::short_type_name;
+use super::security_level as sl;
+
+// The message a panic was raised with, if it was a string
+pub(crate) fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default()
+}
+
+/// A panic caught in a labeled thread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecPanic {
+    message: String,
+}
+
+impl SecPanic {
+    /// The message the thread panicked with, or an empty string if it wasn't a string.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for SecPanic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "labeled thread panicked: {}", self.message)
+    }
+}
+
+impl Error for SecPanic {}
+
+/// Spawns a thread computing a `Sec<S, A>`.
+///
+/// # Examples
+/// ```
+/// use seclib::prelude::*;
+/// use seclib::sec_thread;
+///
+/// let high = Authority::acquire().unwrap().clearance::<High>();
+///
+/// let salary: Sec<High, u32> = Sec::new(52_000);
+/// let handle = sec_thread::spawn(move || salary.map(|s| s * 12));
+///
+/// assert_eq!(handle.join().reveal(&high), Ok(624_000));
+/// ```
+/// A panic comes back labeled, so it takes clearance to tell it happened:
+/// ```
+/// use seclib::prelude::*;
+/// use seclib::sec_thread;
+///
+/// let high = Authority::acquire().unwrap().clearance::<High>();
+///
+/// let secret: Sec<High, Vec<u8>> = Sec::new(vec![]);
+/// let handle = sec_thread::spawn(move || secret.map(|v| v[0]));
+///
+/// let result: Sec<High, Result<u8, _>> = handle.join();
+/// assert!(result.reveal(&high).unwrap_err().message().contains("out of bounds"));
+/// ```
+pub fn spawn<S, A, F>(f: F) -> SecJoinHandle<S, A>
+where
+    S: sl::SecurityLevel,
+    A: Send + 'static,
+    F: FnOnce() -> Sec<S, A> + Send + 'static,
+{
+    let handle = thread::spawn(move || {
+        panic::catch_unwind(AssertUnwindSafe(|| f().data))
+            .map_err(|payload| SecPanic { message: panic_message(payload) })
+    });
+    SecJoinHandle { security_level: PhantomData, handle }
+}
+
+/// A handle to a labeled thread, created by `sec_thread::spawn`, `Sec::spawn_map`, or `Sec::spawn_and_then`.
+pub struct SecJoinHandle<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    security_level: PhantomData<S>,
+    handle: thread::JoinHandle<Result<A, SecPanic>>,
+}
+
+impl<S, A> SecJoinHandle<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    /// Waits for the thread to finish, and returns its result, or its panic, at level `S`.
+    pub fn join(self) -> Sec<S, Result<A, SecPanic>> {
+        Sec::new(self.handle.join().unwrap_or_else(|payload| Err(SecPanic { message: panic_message(payload) })))
+    }
+}
+
+impl<S, A> fmt::Debug for SecJoinHandle<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecJoinHandle<{}>(..)", short_type_name::<S>())
+    }
+}
+
+impl<S, A> Sec<S, A>
+where
+    S: sl::SecurityLevel,
+    A: Send + 'static,
+{
+    /// Like `map`, but runs `f` on a new thread.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let handles: Vec<_> = (1..=3)
+    ///     .map(|i| Sec::<High, u64>::new(i).spawn_map(|i| i * 10))
+    ///     .collect();
+    /// let results: Sec<High, Vec<_>> = handles.into_iter().map(|handle| handle.join()).collect();
+    ///
+    /// assert_eq!(results.reveal(&high), vec![Ok(10), Ok(20), Ok(30)]);
+    /// ```
+    pub fn spawn_map<B, F>(self, f: F) -> SecJoinHandle<S, B>
+    where
+        B: Send + 'static,
+        F: FnOnce(A) -> B + Send + 'static,
+    {
+        let Sec { data, .. } = self;
+        spawn(move || Sec::new(f(data)))
+    }
+
+    /// Like `and_then`, but runs `f` on a new thread.
+    pub fn spawn_and_then<B, F>(self, f: F) -> SecJoinHandle<S, B>
+    where
+        B: Send + 'static,
+        F: FnOnce(A) -> Sec<S, B> + Send + 'static,
+    {
+        let Sec { data, .. } = self;
+        spawn(move || f(data))
+    }
+}
@@ -2,20 +2,40 @@
 //! A security level can be anything, so long as it implements the `SecurityLevel` trait.
 //! 
 //! This library provides two example security levels: `Low` and `High`, though one could reasonably implement something like `User`, `Moderator`, and `Administrator`.
+//! The `security_lattice!` macro takes care of declaring levels like those, along with every `SecurityLevel` impl between them.
 //! 
 //! Note that the security levels are only really used for their types, and thus do not have any functionality.
+//! Looking at data of a given level takes a `Clearance` for it, see the `clearance` module.
+//!
+//! `SecurityLevel` is sealed: it can't be implemented outside of this library, so no one can declare
+//! their own level to be above `High`. Custom levels are declared with `security_lattice!` instead.
 
 use std::fmt::Debug;
 
+pub(crate) mod sealed {
+    /// Implemented alongside every `SecurityLevel` impl, and only reachable from within the library.
+    pub trait Sealed<LessThan> {}
+}
+
 /// SecurityLevel encodes both the relation (L &leq; H) and the fact that something can **be** a security level.
 /// 
 /// `LessThan` represents a security level lower than the current one.
-pub trait SecurityLevel<LessThan = Self>: Debug
+pub trait SecurityLevel<LessThan = Self>: Debug + sealed::Sealed<LessThan>
 where
     LessThan: SecurityLevel,
 {
 }
 
+// Implements `SecurityLevel` for each pair, along with the matching `Sealed`
+macro_rules! ordering {
+    ($($(#[$attr:meta])* $lower:ident <= $higher:ident;)*) => {$(
+        impl sealed::Sealed<$lower> for $higher {}
+
+        $(#[$attr])*
+        impl SecurityLevel<$lower> for $higher {}
+    )*};
+}
+
 /// Low security level (L in Haskell's SecLib)
 #[derive(Debug, Clone, PartialEq)]
 pub struct Low;
@@ -24,11 +44,75 @@ pub struct Low;
 #[derive(Debug, Clone, PartialEq)]
 pub struct High;
 
-/// Implements L &leq; L at the type level
-impl SecurityLevel for Low {}
+ordering! {
+    /// Implements L &leq; L at the type level
+    Low <= Low;
+    /// Implements L &leq; H at the type level
+    Low <= High;
+    /// Implements H &leq; H at the type level
+    High <= High;
+}
+
+/// Join encodes the least upper bound of two security levels at the type level.
+///
+/// `Output` is guaranteed to be &geq; both levels, which is what makes it safe to put data of either level into it.
+pub trait Join<Other = Self>: SecurityLevel + Sized
+where
+    Other: SecurityLevel,
+{
+    type Output: SecurityLevel + SecurityLevel<Self> + SecurityLevel<Other>;
+}
+
+/// Meet encodes the greatest lower bound of two security levels at the type level.
+pub trait Meet<Other = Self>: SecurityLevel + Sized
+where
+    Other: SecurityLevel,
+{
+    type Output: SecurityLevel;
+}
+
+/// The least upper bound of `A` and `B`.
+pub type JoinOf<A, B> = <A as Join<B>>::Output;
 
-/// Implements L &leq; H at the type level
-impl SecurityLevel<Low> for High {}
+/// The greatest lower bound of `A` and `B`.
+pub type MeetOf<A, B> = <A as Meet<B>>::Output;
 
-/// Implements H &leq; H at the type level
-impl SecurityLevel for High {}
\ No newline at end of file
+/// Implements L &vee; L = L
+impl Join for Low {
+    type Output = Low;
+}
+
+/// Implements L &vee; H = H
+impl Join<High> for Low {
+    type Output = High;
+}
+
+/// Implements H &vee; L = H
+impl Join<Low> for High {
+    type Output = High;
+}
+
+/// Implements H &vee; H = H
+impl Join for High {
+    type Output = High;
+}
+
+/// Implements L &wedge; L = L
+impl Meet for Low {
+    type Output = Low;
+}
+
+/// Implements L &wedge; H = L
+impl Meet<High> for Low {
+    type Output = Low;
+}
+
+/// Implements H &wedge; L = L
+impl Meet<Low> for High {
+    type Output = Low;
+}
+
+/// Implements H &wedge; H = H
+impl Meet for High {
+    type Output = High;
+}
@@ -0,0 +1,400 @@
+//! Property testing of noninterference for code built on `Sec`.
+//!
+//! A function taking secret and public inputs is *noninterfering* if its public output only depends on the public input.
+//! `Noninterference` checks that by running the function on many pairs of secret inputs with the same public input,
+//! and comparing the outputs. A panic counts as an output too, so code that panics depending on a secret is caught as well.
+//!
+//! Inputs are generated with the small `Rng` included here, through the `Arbitrary` trait.
+//! A failing case is shrunk to a smaller one before it's reported.
+//!
+//! Note that panics are caught, but the panic hook still prints them as usual.
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use super::Sec;
+use super::sec_thread::panic_message;
+use super::security_level as sl;
+
+/// A small, fast, deterministic random number generator (xorshift64*). Not suitable for cryptography.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Constructor. The same seed always gives the same numbers.
+    pub fn new(seed: u64) -> Self {
+        // the state must never be zero, or it stays zero
+        Rng { state: (seed ^ 0x9E37_79B9_7F4A_7C15) | 1 }
+    }
+
+    /// The next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A random number in `0..bound`, or 0 if `bound` is 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            0
+        } else {
+            self.next_u64() % bound
+        }
+    }
+
+    /// A random `bool`.
+    pub fn coin(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+}
+
+/// Types that can be generated at random, and shrunk to simpler values.
+pub trait Arbitrary: Clone + fmt::Debug + Sized {
+    /// A random value.
+    fn arbitrary(rng: &mut Rng) -> Self;
+
+    /// Simpler values to try in place of this one, simplest first.
+    fn shrink(&self) -> Vec<Self> {
+        Vec::new()
+    }
+}
+
+impl Arbitrary for () {
+    fn arbitrary(_: &mut Rng) -> Self {}
+}
+
+impl Arbitrary for bool {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        rng.coin()
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        if *self { vec![false] } else { Vec::new() }
+    }
+}
+
+macro_rules! arbitrary_unsigned {
+    ($($t:ty)*) => {$(
+        impl Arbitrary for $t {
+            fn arbitrary(rng: &mut Rng) -> Self {
+                rng.next_u64() as $t
+            }
+
+            // 0, then ever closer to the value itself
+            fn shrink(&self) -> Vec<Self> {
+                let mut candidates = vec![0];
+                let mut distance = *self / 2;
+                while distance != 0 {
+                    candidates.push(*self - distance);
+                    distance /= 2;
+                }
+                candidates.push(self.saturating_sub(1));
+                candidates.dedup();
+                candidates.retain(|c| c != self);
+                candidates
+            }
+        }
+    )*};
+}
+
+macro_rules! arbitrary_signed {
+    ($($t:ty)*) => {$(
+        impl Arbitrary for $t {
+            fn arbitrary(rng: &mut Rng) -> Self {
+                rng.next_u64() as $t
+            }
+
+            // 0, the positive counterpart, then ever closer to the value itself
+            fn shrink(&self) -> Vec<Self> {
+                let mut candidates = vec![0];
+                if *self < 0 {
+                    candidates.push(self.saturating_neg());
+                }
+                let mut distance = *self / 2;
+                while distance != 0 {
+                    candidates.push(*self - distance);
+                    distance /= 2;
+                }
+                candidates.push(*self - self.signum());
+                candidates.dedup();
+                candidates.retain(|c| c != self);
+                candidates
+            }
+        }
+    )*};
+}
+
+arbitrary_unsigned!(u8 u16 u32 u64 usize);
+arbitrary_signed!(i8 i16 i32 i64 isize);
+
+impl Arbitrary for char {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        // mostly printable ASCII, to keep counterexamples readable
+        if rng.below(8) == 0 {
+            std::char::from_u32(rng.below(0x11_0000) as u32).unwrap_or('?')
+        } else {
+            (b' ' + rng.below(95) as u8) as char
+        }
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        if *self == 'a' { Vec::new() } else { vec!['a'] }
+    }
+}
+
+impl<T> Arbitrary for Vec<T>
+where
+    T: Arbitrary,
+{
+    fn arbitrary(rng: &mut Rng) -> Self {
+        let len = rng.below(16);
+        (0..len).map(|_| T::arbitrary(rng)).collect()
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        let mut candidates = vec![Vec::new(), self[..self.len() / 2].to_vec()];
+        for i in 0..self.len() {
+            let mut shorter = self.clone();
+            shorter.remove(i);
+            candidates.push(shorter);
+        }
+        for (i, item) in self.iter().enumerate() {
+            for simpler in item.shrink() {
+                let mut candidate = self.clone();
+                candidate[i] = simpler;
+                candidates.push(candidate);
+            }
+        }
+        candidates
+    }
+}
+
+impl Arbitrary for String {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        Vec::<char>::arbitrary(rng).into_iter().collect()
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        self.chars().collect::<Vec<_>>().shrink().into_iter().map(|chars| chars.into_iter().collect()).collect()
+    }
+}
+
+impl<T> Arbitrary for Option<T>
+where
+    T: Arbitrary,
+{
+    fn arbitrary(rng: &mut Rng) -> Self {
+        if rng.below(4) == 0 { None } else { Some(T::arbitrary(rng)) }
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        match *self {
+            None => Vec::new(),
+            Some(ref value) => Some(None).into_iter().chain(value.shrink().into_iter().map(Some)).collect(),
+        }
+    }
+}
+
+impl<A, B> Arbitrary for (A, B)
+where
+    A: Arbitrary,
+    B: Arbitrary,
+{
+    fn arbitrary(rng: &mut Rng) -> Self {
+        (A::arbitrary(rng), B::arbitrary(rng))
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        let (ref a, ref b) = *self;
+        let firsts = a.shrink().into_iter().map(|a| (a, b.clone()));
+        let seconds = b.shrink().into_iter().map(|b| (a.clone(), b));
+        firsts.chain(seconds).collect()
+    }
+}
+
+/// What running the function under test resulted in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome<O> {
+    Returned(O),
+    /// The function panicked with the given message.
+    Panicked(String),
+}
+
+/// Two secret inputs, and a public one, for which the outputs differ.
+#[derive(Debug, Clone)]
+pub struct Counterexample<H, L, O> {
+    pub low: L,
+    pub high: (H, H),
+    pub outcomes: (Outcome<O>, Outcome<O>),
+}
+
+impl<H, L, O> fmt::Display for Counterexample<H, L, O>
+where
+    H: fmt::Debug,
+    L: fmt::Debug,
+    O: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "noninterference violated for the public input {:?}:", self.low)?;
+        writeln!(f, "  secret {:?} gives {:?}", self.high.0, self.outcomes.0)?;
+        write!(f, "  secret {:?} gives {:?}", self.high.1, self.outcomes.1)
+    }
+}
+
+/// Checks a function for noninterference.
+///
+/// # Examples
+/// ```
+/// use seclib::prelude::*;
+/// use seclib::testing::Noninterference;
+///
+/// // the public output doesn't depend on the salary, only the secret one does
+/// fn raise(salary: Sec<High, u32>, percent: u8) -> (Sec<High, u64>, String) {
+///     (salary.map(|s| s as u64 * (100 + percent as u64) / 100), format!("raised by {}%", percent))
+/// }
+///
+/// let result = Noninterference::new().check(|salary, percent| raise(salary, percent).1);
+/// assert!(result.is_ok());
+/// ```
+/// Revealing the salary, and letting it decide the public output, is caught:
+/// ```
+/// use seclib::prelude::*;
+/// use seclib::testing::Noninterference;
+///
+/// let high = Authority::acquire().unwrap().clearance::<High>();
+/// let is_rich = |salary: Sec<High, u32>, threshold: u32| salary.reveal(&high) > threshold;
+///
+/// let counterexample = Noninterference::new().check(is_rich).unwrap_err();
+/// assert_ne!(counterexample.outcomes.0, counterexample.outcomes.1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Noninterference {
+    cases: usize,
+    seed: u64,
+}
+
+impl Default for Noninterference {
+    fn default() -> Self {
+        Noninterference { cases: 256, seed: 0x5EC11B }
+    }
+}
+
+impl Noninterference {
+    /// Constructor. Checks 256 cases, with a fixed seed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of cases to check.
+    pub fn cases(mut self, cases: usize) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    /// Sets the seed of the random number generator.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Runs `f` on pairs of random secret inputs with the same random public input,
+    /// and returns the smallest case it could find where the two outcomes differ.
+    pub fn check<S, H, L, O, F>(&self, f: F) -> Result<(), Counterexample<H, L, O>>
+    where
+        S: sl::SecurityLevel,
+        H: Arbitrary,
+        L: Arbitrary,
+        O: PartialEq + fmt::Debug,
+        F: Fn(Sec<S, H>, L) -> O,
+    {
+        let mut rng = Rng::new(self.seed);
+
+        for _ in 0..self.cases {
+            let low = L::arbitrary(&mut rng);
+            let high = (H::arbitrary(&mut rng), H::arbitrary(&mut rng));
+
+            if let Some(counterexample) = run(&f, low, high) {
+                return Err(shrink(&f, counterexample));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Like `check`, but panics with the counterexample.
+    pub fn assert<S, H, L, O, F>(&self, f: F)
+    where
+        S: sl::SecurityLevel,
+        H: Arbitrary,
+        L: Arbitrary,
+        O: PartialEq + fmt::Debug,
+        F: Fn(Sec<S, H>, L) -> O,
+    {
+        if let Err(counterexample) = self.check(f) {
+            panic!("{}", counterexample);
+        }
+    }
+}
+
+fn outcome<S, H, L, O, F>(f: &F, high: H, low: L) -> Outcome<O>
+where
+    S: sl::SecurityLevel,
+    F: Fn(Sec<S, H>, L) -> O,
+{
+    panic::catch_unwind(AssertUnwindSafe(|| f(Sec::new(high), low)))
+        .map(Outcome::Returned)
+        .unwrap_or_else(|payload| Outcome::Panicked(panic_message(payload)))
+}
+
+// Runs both cases, returning them if the outcomes differ
+fn run<S, H, L, O, F>(f: &F, low: L, high: (H, H)) -> Option<Counterexample<H, L, O>>
+where
+    S: sl::SecurityLevel,
+    H: Arbitrary,
+    L: Arbitrary,
+    O: PartialEq,
+    F: Fn(Sec<S, H>, L) -> O,
+{
+    let outcomes = (outcome(f, high.0.clone(), low.clone()), outcome(f, high.1.clone(), low.clone()));
+
+    if outcomes.0 == outcomes.1 {
+        None
+    } else {
+        Some(Counterexample { low, high, outcomes })
+    }
+}
+
+// Replaces one input at a time with a simpler one that still fails, until none of them can be simplified any further
+fn shrink<S, H, L, O, F>(f: &F, mut counterexample: Counterexample<H, L, O>) -> Counterexample<H, L, O>
+where
+    S: sl::SecurityLevel,
+    H: Arbitrary,
+    L: Arbitrary,
+    O: PartialEq,
+    F: Fn(Sec<S, H>, L) -> O,
+{
+    // shrinking always terminates for the impls here, but a user's impl might cycle
+    for _ in 0..1_000 {
+        let Counterexample { ref low, ref high, .. } = counterexample;
+
+        let candidates = low.shrink().into_iter().map(|low| (low, high.clone()))
+            .chain(high.0.shrink().into_iter().map(|h| (low.clone(), (h, high.1.clone()))))
+            .chain(high.1.shrink().into_iter().map(|h| (low.clone(), (high.0.clone(), h))));
+
+        let smaller = candidates.filter_map(|(low, high)| run(f, low, high)).next();
+        match smaller {
+            Some(smaller) => counterexample = smaller,
+            None => break,
+        }
+    }
+
+    counterexample
+}
@@ -1,4 +1,26 @@
+use std::io;
+
 use super::prelude::*;
+use clearance::{Clearance, DynClearance};
+use dynamic::Label;
+use principal::{Principal, Privilege};
+use trusted;
+
+// The authority can only be acquired once per process, so the tests mint their clearances and privileges directly
+const HIGH: &Clearance<High> = &Clearance::mint();
+const LOW: &Clearance<Low> = &Clearance::mint();
+
+fn clearance<S: SecurityLevel>() -> Clearance<S> {
+    Clearance::mint()
+}
+
+fn dyn_clearance<L: Label>(label: L) -> DynClearance<L> {
+    DynClearance { label }
+}
+
+fn privilege(principal: &Principal) -> Privilege {
+    Privilege::mint(principal.clone())
+}
 
 #[test]
 fn test_map() {
@@ -7,7 +29,7 @@ fn test_map() {
 
     let expected: Sec<High, String> = Sec::new("I'm Safe!".into());
 
-    assert_eq!(result, expected);
+    assert!(result.labeled_eq(&expected).reveal(HIGH));
 }
 
 #[test]
@@ -20,7 +42,7 @@ fn test_and_then() {
     let result = data.and_then(f1);
     let expected: Sec<High, i32> = 6.into();
 
-    assert_eq!(result, expected);
+    assert!(result.labeled_eq(&expected).reveal(HIGH));
 
     fn f2(i: i32) -> Sec<Low, i32> {
         (i + 2).into()
@@ -30,26 +52,26 @@ fn test_and_then() {
     let result = data.and_then(f2);
     let expected: Sec<Low, i32> = 7.into();
 
-    assert_eq!(result, expected);
+    assert!(result.labeled_eq(&expected).reveal(HIGH));
 }
 
 #[test]
 fn test_reveal() {
     // testing high <= high
     let data: Sec<High, i32> = 12.into();
-    let result = data.reveal(High);
+    let result = data.reveal(HIGH);
 
     assert_eq!(result, 12);
 
     // testing low <= high
     let data: Sec<Low, i32> = 13.into();
-    let result = data.reveal(High);
+    let result = data.reveal(HIGH);
 
     assert_eq!(result, 13);
 
     // testing low <= low
     let data: Sec<Low, i32> = 14.into();
-    let result = data.reveal(Low);
+    let result = data.reveal(LOW);
 
     assert_eq!(result, 14);
 
@@ -63,26 +85,1588 @@ fn test_lift() {
     let result = data.lift(High);
     let expected: Sec<High, String> = String::from("Attack at dawn").into();
 
-    assert_eq!(result, expected);
+    assert!(result.labeled_eq(&expected).reveal(HIGH));
 
     // .. from low to low
     let data: Sec<Low, String> = String::from("Attack at noon").into();
     let result = data.lift(Low);
     let expected: Sec<Low, String> = String::from("Attack at noon").into();
 
-    assert_eq!(result, expected);
+    assert!(result.labeled_eq(&expected).reveal(HIGH));
 
     // .. from high to high
     let data: Sec<High, String> = String::from("Attack at night").into();
     let result = data.lift(High);
     let expected: Sec<High, String> = String::from("Attack at night").into();
 
-    assert_eq!(result, expected);
+    assert!(result.labeled_eq(&expected).reveal(HIGH));
 
     // Does not compile, as intended!
     // let data: Sec<High, String> = String::from("Attack now!").into();
     // let result = data.lift(Low);
     // let expected: Sec<Low, String> = String::from("Attack now!").into();
 
-    // assert_eq!(result, expected);
-}
\ No newline at end of file
+    // assert!(result.labeled_eq(&expected).reveal(HIGH));
+}
+
+#[test]
+fn test_debug_is_redacted() {
+    let data: Sec<High, String> = String::from("Attack at dawn").into();
+
+    assert_eq!(format!("{:?}", data), "Sec<High>(<redacted>)");
+    assert_eq!(format!("{:#?}", data), "Sec<High>(<redacted>)");
+
+    let data: Sec<Low, i32> = 42.into();
+
+    assert_eq!(format!("{:?}", data), "Sec<Low>(<redacted>)");
+}
+
+#[test]
+fn test_display_is_redacted() {
+    let data: Sec<High, String> = String::from("Attack at dawn").into();
+
+    assert_eq!(format!("{}", data), "Sec<High>(<redacted>)");
+    assert_eq!(format!("{:>30}", data), "Sec<High>(<redacted>)");
+    assert_eq!(data.to_string(), "Sec<High>(<redacted>)");
+}
+
+#[test]
+fn test_nested_formatting_is_redacted() {
+    // the payload must not show up no matter where the `Sec` is buried
+    let data: Vec<Option<Sec<High, String>>> = vec![Some(String::from("Attack at dawn").into()), None];
+    let output = format!("{:?} {:#?}", data, data);
+
+    assert!(!output.contains("Attack at dawn"));
+
+    let data: Sec<High, Sec<Low, i32>> = Sec::new(1337.into());
+    let output = format!("{:?} {}", data, data);
+
+    assert_eq!(output, "Sec<High>(<redacted>) Sec<High>(<redacted>)");
+
+    // `Debug` needs no bound on the payload at all
+    struct Opaque;
+    let data: Sec<High, Opaque> = Opaque.into();
+
+    assert_eq!(format!("{:?}", data), "Sec<High>(<redacted>)");
+}
+
+#[test]
+fn test_unredacted() {
+    // testing high <= high
+    let data: Sec<High, String> = String::from("Attack at dawn").into();
+
+    assert_eq!(format!("{}", data.unredacted(HIGH)), "Attack at dawn");
+    assert_eq!(format!("{:?}", data.unredacted(HIGH)), "\"Attack at dawn\"");
+
+    // testing low <= high
+    let data: Sec<Low, i32> = 12.into();
+
+    assert_eq!(format!("{:>4}", data.unredacted(HIGH)), "  12");
+
+    // testing low <= low
+    assert_eq!(format!("{}", data.unredacted(LOW)), "12");
+
+    // Does not compile, as intended!
+    // let data: Sec<High, i32> = 12.into();
+    // format!("{}", data.unredacted(LOW));
+}
+
+#[test]
+fn test_labeled_comparisons() {
+    use std::cmp::Ordering;
+    use std::collections::hash_map::DefaultHasher;
+
+    let a: Sec<High, i32> = 1.into();
+    let b: Sec<High, i32> = 2.into();
+
+    // the results are of type `Sec<High, _>`, so they need revealing
+    assert!(!a.labeled_eq(&b).reveal(HIGH));
+    assert!(a.labeled_eq(&a.clone()).reveal(HIGH));
+    assert_eq!(a.labeled_cmp(&b).reveal(HIGH), Ordering::Less);
+    assert_eq!(b.labeled_partial_cmp(&a).reveal(HIGH), Some(Ordering::Greater));
+
+    let ha = a.labeled_hash::<DefaultHasher>();
+    let hb = a.clone().labeled_hash::<DefaultHasher>();
+
+    assert!(ha.labeled_eq(&hb).reveal(HIGH));
+}
+
+#[test]
+fn test_cleared_keys() {
+    use std::collections::{BTreeMap, HashSet};
+
+    let mut salaries = BTreeMap::new();
+    salaries.insert(Sec::<High, u32>::new(52_000).cleared(HIGH), "alice");
+    salaries.insert(Sec::<High, u32>::new(31_000).cleared(HIGH), "bob");
+    salaries.insert(Sec::<Low, u32>::new(40_000).lift(High).cleared(HIGH), "carol");
+
+    let names: Vec<&str> = salaries.values().cloned().collect();
+    assert_eq!(names, vec!["bob", "carol", "alice"]);
+
+    let mut codes = HashSet::new();
+    codes.insert(Sec::<Low, &str>::new("1234").cleared(HIGH));
+    codes.insert(Sec::<Low, &str>::new("1234").cleared(HIGH));
+    codes.insert(Sec::<Low, &str>::new("4321").cleared(HIGH));
+
+    assert_eq!(codes.len(), 2);
+
+    // keys stay redacted, and come back out as `Sec`s
+    let key = Sec::<High, &str>::new("hunter2").cleared(HIGH);
+    assert_eq!(format!("{:?}", key), "Cleared<High>(Sec<High>(<redacted>))");
+    assert_eq!(key.into_sec().reveal(HIGH), "hunter2");
+
+    // Does not compile, as intended!
+    // Sec::<High, u32>::new(52_000).cleared(LOW);
+}
+
+#[test]
+fn test_zip_with() {
+    // testing low join low
+    let a: Sec<Low, i32> = 1.into();
+    let b: Sec<Low, i32> = 2.into();
+    let result: Sec<Low, i32> = a.zip_with(b, |a, b| a + b);
+
+    assert_eq!(result.reveal(LOW), 3);
+
+    // .. low join high, and high join low
+    let a: Sec<Low, i32> = 3.into();
+    let b: Sec<High, i32> = 4.into();
+    let result: Sec<High, i32> = a.zip_with(b, |a, b| a * b);
+
+    assert_eq!(result.reveal(HIGH), 12);
+
+    let a: Sec<High, &str> = "Attack at ".into();
+    let b: Sec<Low, &str> = "dawn".into();
+    let result: Sec<High, String> = a.zip_with(b, |a, b| format!("{}{}", a, b));
+
+    assert_eq!(result.reveal(HIGH), "Attack at dawn");
+
+    // Does not compile, as intended!
+    // let a: Sec<Low, i32> = 3.into();
+    // let b: Sec<High, i32> = 4.into();
+    // let result: Sec<Low, i32> = a.zip_with(b, |a, b| a * b);
+}
+
+#[test]
+fn test_meet() {
+    fn lower<A, S1, S2>(data: A, _: S1, _: S2) -> Sec<MeetOf<S1, S2>, A>
+    where
+        S1: Meet<S2>,
+        S2: SecurityLevel,
+    {
+        Sec::new(data)
+    }
+
+    let result: Sec<Low, i32> = lower(1, Low, High);
+    assert_eq!(result.reveal(LOW), 1);
+
+    let result: Sec<Low, i32> = lower(2, High, Low);
+    assert_eq!(result.reveal(LOW), 2);
+
+    let result: Sec<High, i32> = lower(3, High, High);
+    assert_eq!(result.reveal(HIGH), 3);
+}
+
+#[test]
+fn test_sec_io_map_and_then() {
+    let data: Sec<Low, i32> = 20.into();
+    let secret: Sec<High, i32> = 22.into();
+
+    let program: SecIO<High, i32> = SecIO::value(data)
+        .and_then(move |i| SecIO::value(secret).map(move |j| i + j))
+        .map(|i| i * 2);
+
+    assert_eq!(program.run().reveal(HIGH), 84);
+
+    // Does not compile, as intended!
+    // let secret: Sec<High, i32> = 22.into();
+    // let program: SecIO<Low, i32> = SecIO::value(secret);
+}
+
+#[test]
+fn test_sec_io_is_lazy() {
+    let sink: Sink<High, Vec<u8>> = Sink::new(Vec::new());
+    let program: SecIO<High, _> = SecIO::write(&sink, "written");
+
+    // nothing has happened yet
+    let sink = sink.into_inner(HIGH).unwrap_err();
+
+    program.run().reveal(HIGH).unwrap();
+    assert_eq!(sink.into_inner(HIGH).unwrap(), b"written");
+}
+
+#[test]
+fn test_sec_io_write() {
+    let public: Sink<Low, Vec<u8>> = Sink::new(Vec::new());
+    let secret: Sink<High, Vec<u8>> = Sink::new(Vec::new());
+
+    // a low computation may write to both
+    let s = secret.clone();
+    let program: SecIO<Low, io::Result<()>> = SecIO::write(&public, "low ")
+        .and_then(move |_| SecIO::write(&s, "low "));
+
+    program.run().reveal(LOW).unwrap();
+
+    // a high one only to the high sink
+    let s = secret.clone();
+    let data: Sec<High, String> = String::from("high").into();
+    let program: SecIO<High, io::Result<()>> = SecIO::value(data).and_then(move |d| SecIO::write(&s, d));
+
+    program.run().reveal(HIGH).unwrap();
+
+    assert_eq!(public.into_inner(LOW).unwrap(), b"low ");
+    assert_eq!(secret.into_inner(HIGH).unwrap(), b"low high");
+
+    // Does not compile, as intended!
+    // let program: SecIO<High, io::Result<()>> = SecIO::write(&public, "high");
+    // let written = secret.into_inner(LOW);
+}
+
+#[test]
+fn test_sec_io_plug() {
+    let sink: Sink<High, Vec<u8>> = Sink::new(Vec::new());
+    let s = sink.clone();
+
+    // the high part runs as part of the low computation, but its result stays high
+    let secret: Sec<High, &str> = "classified".into();
+    let program: SecIO<Low, Sec<High, usize>> = SecIO::value(secret)
+        .and_then(move |d| SecIO::write(&s, d).map(move |_| d.len()))
+        .plug(Low);
+
+    let result: Sec<Low, Sec<High, usize>> = program.run();
+
+    assert_eq!(result.reveal(LOW).reveal(HIGH), 10);
+    assert_eq!(sink.into_inner(HIGH).unwrap(), b"classified");
+    assert_eq!(format!("{:?}", SecIO::<High, i32>::new(1)), "SecIO<High>(..)");
+}
+
+#[test]
+fn test_file_registry() {
+    use file::{FileError, FileRegistry};
+
+    let mut registry = FileRegistry::new();
+    registry
+        .register::<Low>("/srv/app")
+        .register::<High>("/srv/app/config")
+        .register::<Low>("/srv/app/config/public.toml");
+
+    // the longest registered path wins
+    assert!(registry.open::<Low>("/srv/app/index.html").is_ok());
+    assert!(registry.open::<High>("/srv/app/config/secrets.toml").is_ok());
+    assert!(registry.open::<Low>("/srv/app/config/public.toml").is_ok());
+
+    // paths are compared by component, not by prefix
+    assert_eq!(
+        registry.open::<High>("/srv/app/configuration").unwrap_err(),
+        FileError::WrongLevel { path: "/srv/app/configuration".into(), expected: "High".into(), found: "Low".into() }
+    );
+
+    assert_eq!(
+        registry.open::<Low>("/etc/passwd").unwrap_err(),
+        FileError::Unregistered("/etc/passwd".into())
+    );
+
+    assert_eq!(
+        registry.open::<Low>("/srv/app/../app/config/secrets.toml").unwrap_err(),
+        FileError::ParentDir("/srv/app/../app/config/secrets.toml".into())
+    );
+
+    // registering again replaces the level
+    registry.register::<High>("/srv/app/config/public.toml");
+    assert!(registry.open::<Low>("/srv/app/config/public.toml").is_err());
+}
+
+#[test]
+fn test_sec_file_read_write() {
+    use std::{env, fs, process};
+    use file::FileRegistry;
+
+    let dir = env::temp_dir().join(format!("seclib-test-files-{}", process::id()));
+    let (public, secrets) = (dir.join("public"), dir.join("secrets"));
+    fs::create_dir_all(&public).unwrap();
+    fs::create_dir_all(&secrets).unwrap();
+
+    let mut registry = FileRegistry::new();
+    registry.register::<Low>(&public).register::<High>(&secrets);
+
+    let key = registry.open::<High>(secrets.join("key")).unwrap();
+    let index = registry.open::<Low>(public.join("index.html")).unwrap();
+
+    // low data goes anywhere
+    let greeting: Sec<Low, &str> = "hello".into();
+    index.write_file(greeting.clone()).run().reveal(LOW).unwrap();
+    key.write_file(greeting).run().reveal(HIGH).unwrap();
+
+    // high data only goes into high files
+    let secret: Sec<High, String> = String::from("hunter2").into();
+    key.write_file(secret).run().reveal(HIGH).unwrap();
+
+    // reading from a low computation keeps the contents labeled
+    let contents: Sec<High, io::Result<Vec<u8>>> = key.read_file::<Low>().run().reveal(LOW);
+    assert_eq!(contents.reveal(HIGH).unwrap(), b"hunter2");
+
+    let contents: Sec<Low, io::Result<Vec<u8>>> = index.read_file::<Low>().run().reveal(LOW);
+    assert_eq!(contents.reveal(LOW).unwrap(), b"hello");
+
+    // and so does whether it could be read at all
+    let missing = registry.open::<High>(secrets.join("missing")).unwrap();
+    let contents: Sec<High, io::Result<Vec<u8>>> = missing.read_file::<Low>().run().reveal(LOW);
+    assert!(contents.reveal(HIGH).is_err());
+
+    // a link in a public directory leading into the secret one is secret too
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(secrets.join("key"), public.join("key")).unwrap();
+        assert!(registry.open::<Low>(public.join("key")).is_err());
+        let key = registry.open::<High>(public.join("key")).unwrap();
+        assert_eq!(key.read_file::<High>().run().reveal(HIGH).reveal(HIGH).unwrap(), b"hunter2");
+
+        std::os::unix::fs::symlink(&secrets, public.join("secrets")).unwrap();
+        assert!(registry.open::<Low>(public.join("secrets").join("new")).is_err());
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    // Does not compile, as intended!
+    // let secret: Sec<High, String> = String::from("hunter2").into();
+    // index.write_file(secret);
+}
+
+#[test]
+fn test_declassify() {
+    use audit;
+    use declassify::Policy;
+
+    struct LastFour;
+
+    impl Declassifier<High, Low> for LastFour {
+        type Input = String;
+        type Output = String;
+
+        fn reason(&self) -> String {
+            "show the last four digits of a card number".into()
+        }
+
+        fn declassify(&self, card: String) -> String {
+            format!("**** {}", &card[card.len() - 4..])
+        }
+    }
+
+    let card: Sec<High, String> = String::from("4111111111111111").into();
+    let line = line!() + 1;
+    let result: Sec<Low, String> = card.declassify(&LastFour, HIGH);
+
+    assert_eq!(result.reveal(LOW), "**** 1111");
+
+    let record = audit::records().into_iter()
+        .find(|r| r.reason == "show the last four digits of a card number")
+        .unwrap();
+
+    assert_eq!(record.from, "High");
+    assert_eq!(record.to, "Low");
+    assert_eq!(record.file, file!());
+    assert_eq!(record.line, line);
+
+    // closures work too
+    let length = Policy::new("test_declassify: length only", |s: String| s.len());
+    let password: Sec<High, String> = String::from("hunter2").into();
+    let result: Sec<Low, usize> = password.declassify(&length, HIGH);
+
+    assert_eq!(result.reveal(LOW), 7);
+    assert!(audit::records().iter().any(|r| r.reason == "test_declassify: length only"));
+
+    // Does not compile, as intended!
+    // let password: Sec<High, String> = String::from("hunter2").into();
+    // let result: Sec<Low, usize> = password.declassify(&length, LOW);
+}
+
+#[test]
+fn test_audit_log_to_file() {
+    use std::{env, fs, process};
+    use audit;
+    use declassify::Policy;
+
+    let path = env::temp_dir().join(format!("seclib-test-audit-{}.log", process::id()));
+    audit::log_to_file(&path).unwrap();
+
+    let policy = Policy::new("test_audit_log_to_file: parity", |i: i32| i % 2 == 0);
+    let data: Sec<High, i32> = 42.into();
+    let line = line!() + 1;
+    let result: Sec<Low, bool> = data.declassify(&policy, HIGH);
+
+    assert!(result.reveal(LOW));
+
+    let log = fs::read_to_string(&path).unwrap();
+    let expected = format!("{}:{}:", file!(), line);
+    let entry = log.lines().find(|l| l.ends_with("test_audit_log_to_file: parity")).unwrap();
+
+    assert!(entry.starts_with(&expected));
+    assert!(entry.contains("declassified High -> Low"));
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_dyn_sec_labels() {
+    use dynamic::{FlowError, Level};
+
+    assert!(Level::Low.can_flow_to(&Level::High));
+    assert!(Level::High.can_flow_to(&Level::High));
+    assert!(!Level::High.can_flow_to(&Level::Low));
+    assert_eq!(Level::Low.join(&Level::High), Level::High);
+    assert_eq!(Level::Low.meet(&Level::High), Level::Low);
+
+    let a = DynSec::new(Level::Low, 20);
+    let b = DynSec::new(Level::High, 22);
+
+    assert_eq!(a.clone().map(|i| i + 1).label(), &Level::Low);
+    assert_eq!(a.clone().zip_with(b.clone(), |a, b| a + b).label(), &Level::High);
+    assert_eq!(a.clone().and_then(Level::High, |_| b.clone()).reveal(&dyn_clearance(Level::High)), Ok(Ok(22)));
+
+    // the result's label is fixed up front, whichever label `f` picks
+    let pick = |secret: bool| a.clone().and_then(Level::Low, move |i| DynSec::new(if secret { Level::High } else { Level::Low }, i));
+    assert_eq!((pick(true).label(), pick(false).label()), (&Level::Low, &Level::Low));
+    assert_eq!(pick(false).reveal(&dyn_clearance(Level::Low)), Ok(Ok(20)));
+    assert_eq!(pick(true).reveal(&dyn_clearance(Level::Low)), Ok(Err(FlowError { from: Level::High, to: Level::Low })));
+    assert_eq!(format!("{:?} {}", b, b), "DynSec<High>(<redacted>) DynSec<High>(<redacted>)");
+
+    // raising works, lowering doesn't
+    assert_eq!(a.raise(Level::High).unwrap().label(), &Level::High);
+
+    let error = b.raise(Level::Low).unwrap_err();
+    assert_eq!(error, FlowError { from: Level::High, to: Level::Low });
+    assert_eq!(error.to_string(), "data labeled High can't flow to Low");
+}
+
+#[test]
+fn test_dyn_sec_conversions() {
+    use std::convert::TryFrom;
+    use dynamic::Level;
+
+    // static to dynamic always works
+    let data: Sec<High, i32> = 1.into();
+    let data: DynSec<i32> = data.into();
+
+    assert_eq!(data.label(), &Level::High);
+
+    // dynamic to static is checked
+    assert!(data.into_sec::<Low>().is_err());
+
+    let data = DynSec::new(Level::High, 2);
+    let data: Sec<High, i32> = Sec::try_from(data).unwrap();
+    assert_eq!(data.reveal(HIGH), 2);
+
+    let data = DynSec::new(Level::Low, 3);
+    let data: Sec<High, i32> = data.into_sec().unwrap();
+    assert_eq!(data.reveal(HIGH), 3);
+
+    // round trip
+    let data: Sec<Low, i32> = 4.into();
+    let data: Sec<Low, i32> = DynSec::from_sec(data).into_sec().unwrap();
+    assert_eq!(data.reveal(LOW), 4);
+}
+
+#[test]
+fn test_lio_floating_label() {
+    use dynamic::Level;
+    use lio::{Lio, LioError};
+
+    let mut lio = Lio::new(Level::Low, &dyn_clearance(Level::High)).unwrap();
+    assert_eq!(lio.current(), &Level::Low);
+
+    // unlabeling low data leaves the label be
+    assert_eq!(lio.unlabel(DynSec::new(Level::Low, 1)).unwrap(), 1);
+    assert_eq!(lio.current(), &Level::Low);
+
+    // unlabeling high data raises it
+    let secret: Sec<High, i32> = 2.into();
+    assert_eq!(lio.unlabel_sec(secret).unwrap(), 2);
+    assert_eq!(lio.current(), &Level::High);
+
+    // after which labeling data low is no longer allowed
+    assert!(matches!(lio.label(Level::Low, 3), Err(LioError::BelowCurrent { .. })));
+    assert!(lio.label_sec::<Low, _>(3).is_err());
+    assert_eq!(lio.label_sec::<High, _>(3).unwrap().reveal(HIGH), 3);
+
+    // the clearance caps the current label
+    let mut lio = Lio::new(Level::Low, &dyn_clearance(Level::Low)).unwrap();
+    let secret: Sec<High, i32> = 4.into();
+
+    match lio.unlabel_sec(secret) {
+        Err(LioError::ExceedsClearance { label, clearance }) => {
+            assert_eq!((label, clearance), (Level::High, Level::Low));
+        }
+        _ => panic!("expected the clearance to be exceeded"),
+    }
+    assert_eq!(lio.current(), &Level::Low);
+
+    assert!(Lio::new(Level::High, &dyn_clearance(Level::Low)).is_err());
+}
+
+#[test]
+fn test_lio_write() {
+    use dynamic::Level;
+    use lio::{Lio, LioError, LioSink};
+
+    let mut public = LioSink::new(Level::Low, Vec::new());
+    let mut secret = LioSink::new(Level::High, Vec::new());
+
+    let result = Lio::new(Level::Low, &dyn_clearance(Level::High)).unwrap().run(|lio| {
+        lio.write(&mut public, b"public")?;
+        lio.write(&mut secret, b"public ")?;
+
+        let password = lio.unlabel(DynSec::new(Level::High, "hunter2"))?;
+        lio.write(&mut secret, password.as_bytes())?;
+
+        // the write down fails, whatever the secret
+        match lio.write(&mut public, password.as_bytes()) {
+            Err(LioError::BelowCurrent { .. }) => Ok(password.len()),
+            _ => panic!("expected the write down to fail"),
+        }
+    });
+
+    assert_eq!(result.label(), &Level::High);
+    assert_eq!(result.reveal(&dyn_clearance(Level::High)).unwrap().unwrap(), 7);
+
+    // formatting a sink doesn't show what was written to it
+    assert_eq!(format!("{:?}", secret), "LioSink<High>(..)");
+
+    assert_eq!(public.into_inner(&dyn_clearance(Level::Low)).unwrap(), b"public");
+    assert_eq!(secret.into_inner(&dyn_clearance(Level::High)).unwrap(), b"public hunter2");
+
+    // the high sink's contents take a high clearance
+    let secret = LioSink::new(Level::High, b"hunter2".to_vec());
+    assert!(secret.into_inner(&dyn_clearance(Level::Low)).is_err());
+}
+
+#[test]
+fn test_lio_run_failure_is_labeled() {
+    use dynamic::Level;
+    use lio::{Lio, LioError, LioSink};
+
+    // the computation fails only when the secret is zero, which must not show below the label it ends up at
+    let attempt = |secret: u32| {
+        let mut public = LioSink::new(Level::Low, Vec::new());
+        let secret: Sec<High, u32> = secret.into();
+
+        Lio::new(Level::Low, &dyn_clearance(Level::High)).unwrap().run(|lio| {
+            if lio.unlabel_sec(secret)? == 0 {
+                lio.write(&mut public, b"zero")?;
+            }
+            Ok(())
+        })
+    };
+
+    let ok = attempt(1);
+    let failed = attempt(0);
+    assert_eq!((ok.label(), failed.label()), (&Level::High, &Level::High));
+    assert_eq!(format!("{:?}", ok), format!("{:?}", failed));
+
+    assert!(ok.reveal(&dyn_clearance(Level::High)).unwrap().is_ok());
+    assert!(matches!(failed.reveal(&dyn_clearance(Level::High)).unwrap(), Err(LioError::BelowCurrent { .. })));
+}
+
+#[test]
+fn test_lio_to_labeled() {
+    use dynamic::Level;
+    use lio::{Lio, LioError, LioSink};
+
+    let mut public = LioSink::new(Level::Low, Vec::new());
+    let secret: Sec<High, i32> = 42.into();
+
+    let result = Lio::new(Level::Low, &dyn_clearance(Level::High)).unwrap().run(|lio| {
+        // the secret is only looked at within `to_labeled`
+        let doubled = lio.to_labeled(Level::High, |inner| Ok(inner.unlabel_sec(secret)? * 2))?;
+
+        assert_eq!(lio.current(), &Level::Low);
+        lio.write(&mut public, b"still low")?;
+
+        Ok(doubled)
+    });
+
+    assert_eq!(result.label(), &Level::Low);
+
+    let doubled = result.reveal(&dyn_clearance(Level::Low)).unwrap().unwrap();
+    assert_eq!(doubled.label(), &Level::High);
+    assert_eq!(doubled.into_sec::<High>().unwrap().reveal(HIGH).unwrap(), 84);
+    assert_eq!(public.into_inner(&dyn_clearance(Level::Low)).unwrap(), b"still low");
+
+    // the inner computation can't rise above the label it was given
+    let mut lio = Lio::new(Level::Low, &dyn_clearance(Level::High)).unwrap();
+    let result = lio.to_labeled(Level::Low, |inner| inner.unlabel(DynSec::new(Level::High, 1))).unwrap();
+
+    assert!(matches!(result.reveal(&dyn_clearance(Level::Low)).unwrap(), Err(LioError::ExceedsClearance { .. })));
+
+    // and only a label outside the context's bounds fails the call itself
+    assert!(lio.to_labeled(Level::High, |_| Ok(())).is_ok());
+    let mut low = Lio::new(Level::Low, &dyn_clearance(Level::Low)).unwrap();
+    assert!(matches!(low.to_labeled(Level::High, |_| Ok(())), Err(LioError::ExceedsClearance { .. })));
+}
+
+#[test]
+fn test_lio_to_labeled_failure_is_labeled() {
+    use dynamic::Level;
+    use lio::{Lio, LioError, LioSink};
+
+    // the inner computation fails only when the secret is zero, which must not show at the current label
+    let attempt = |secret: u32| {
+        let mut public = LioSink::new(Level::Low, Vec::new());
+        let secret: Sec<High, u32> = secret.into();
+
+        let mut lio = Lio::new(Level::Low, &dyn_clearance(Level::High)).unwrap();
+        let quotient = lio.to_labeled(Level::High, |inner| {
+            let divisor = inner.unlabel_sec(secret)?;
+            if divisor == 0 {
+                inner.write(&mut public, b"division by zero")?;
+            }
+            Ok(100 / divisor)
+        });
+
+        // either way, the outer context carries on at the same label
+        assert_eq!(lio.current(), &Level::Low);
+        assert!(public.into_inner(&dyn_clearance(Level::Low)).unwrap().is_empty());
+        quotient.unwrap()
+    };
+
+    let ok = attempt(4);
+    let failed = attempt(0);
+    assert_eq!((ok.label(), failed.label()), (&Level::High, &Level::High));
+    assert_eq!(format!("{:?}", ok), format!("{:?}", failed));
+
+    assert_eq!(ok.reveal(&dyn_clearance(Level::High)).unwrap().unwrap(), 25);
+    assert!(matches!(failed.reveal(&dyn_clearance(Level::High)).unwrap(), Err(LioError::BelowCurrent { .. })));
+}
+
+// A tiny xorshift generator, so property tests don't need any dependencies
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn vec(&mut self) -> Vec<i32> {
+        let len = self.next() % 20;
+        (0..len).map(|_| self.next() as i32).collect()
+    }
+}
+
+// The level is part of the (redacted) `Debug` output, which makes it easy to check at runtime as well
+fn level_of<S: SecurityLevel, A>(sec: &Sec<S, A>) -> String {
+    format!("{:?}", sec)
+}
+
+#[test]
+fn test_prop_sequence_and_traverse() {
+    let mut rng = XorShift(0x5EC11B);
+
+    for _ in 0..500 {
+        let input = rng.vec();
+
+        let secs: Vec<Sec<High, i32>> = input.iter().cloned().map(Sec::new).collect();
+        let result: Sec<High, Vec<i32>> = Sec::sequence(secs);
+        assert_eq!(level_of(&result), "Sec<High>(<redacted>)");
+        assert_eq!(result.reveal(HIGH), input);
+
+        let result: Sec<Low, Vec<i64>> = Sec::traverse(input.clone(), |i| Sec::new(i as i64 * 2));
+        assert_eq!(level_of(&result), "Sec<Low>(<redacted>)");
+        assert_eq!(result.reveal(LOW), input.iter().map(|&i| i as i64 * 2).collect::<Vec<_>>());
+
+        let result: Sec<High, Vec<i32>> = input.iter().cloned().map(Sec::<High, i32>::new).collect();
+        assert_eq!(level_of(&result), "Sec<High>(<redacted>)");
+        assert_eq!(result.reveal(HIGH), input);
+    }
+}
+
+#[test]
+fn test_prop_collect_into_any_collection() {
+    use std::collections::BTreeSet;
+
+    let mut rng = XorShift(0xC0FFEE);
+
+    for _ in 0..500 {
+        let input = rng.vec();
+
+        let result: Sec<High, BTreeSet<i32>> = input.iter().cloned().map(Sec::<High, i32>::new).collect();
+        assert_eq!(level_of(&result), "Sec<High>(<redacted>)");
+        assert_eq!(result.reveal(HIGH), input.iter().cloned().collect::<BTreeSet<_>>());
+    }
+
+    let result: Sec<Low, String> = vec!["a", "b", "c"].into_iter().map(Sec::<Low, &str>::new).collect();
+    assert_eq!(result.reveal(LOW), "abc");
+}
+
+#[test]
+fn test_prop_option_transpositions() {
+    let mut rng = XorShift(0xBADA55);
+
+    for _ in 0..500 {
+        let input = if rng.next() & 1 == 0 { Some(rng.next() as i32) } else { None };
+
+        let result: Sec<High, Option<i32>> = Sec::from_option(input.map(Sec::new));
+        assert_eq!(level_of(&result), "Sec<High>(<redacted>)");
+
+        let back: Option<Sec<High, i32>> = result.transpose(HIGH);
+        assert_eq!(back.map(|sec| {
+            assert_eq!(level_of(&sec), "Sec<High>(<redacted>)");
+            sec.reveal(HIGH)
+        }), input);
+    }
+
+    // Does not compile, as intended!
+    // let data: Sec<High, Option<i32>> = Sec::new(Some(1));
+    // data.transpose(LOW);
+}
+
+#[test]
+fn test_prop_result_transpositions() {
+    let mut rng = XorShift(0xFACADE);
+
+    for _ in 0..500 {
+        let n = rng.next() as i32;
+        let input: Result<i32, String> = if rng.next() & 1 == 0 { Ok(n) } else { Err(n.to_string()) };
+
+        let labeled = input.clone().map(Sec::new).map_err(Sec::new);
+        let result: Sec<Low, Result<i32, String>> = Sec::from_result(labeled);
+        assert_eq!(level_of(&result), "Sec<Low>(<redacted>)");
+
+        // a low `Sec` can be looked into with high clearance too
+        let back: Result<Sec<Low, i32>, Sec<Low, String>> = result.transpose(HIGH);
+        let back = back.map(|sec| {
+            assert_eq!(level_of(&sec), "Sec<Low>(<redacted>)");
+            sec.reveal(LOW)
+        }).map_err(|sec| {
+            assert_eq!(level_of(&sec), "Sec<Low>(<redacted>)");
+            sec.reveal(LOW)
+        });
+        assert_eq!(back, input);
+    }
+}
+
+// An escaping builder for shell commands, which only accepts trusted arguments
+fn shell_command(program: Int<Trusted, &str>, args: Vec<Int<Trusted, String>>) -> Int<Trusted, String> {
+    args.into_iter().fold(program.map(String::from), |command, arg| {
+        command.zip_with(arg, |c, a| format!("{} {}", c, a))
+    })
+}
+
+fn quote(arg: String) -> Result<String, String> {
+    if arg.contains('\'') {
+        Err(format!("refusing to quote {:?}", arg))
+    } else {
+        Ok(format!("'{}'", arg))
+    }
+}
+
+#[test]
+fn test_int_endorse() {
+    let input: Int<Untrusted, String> = Int::new("notes.txt".into());
+    let arg = input.endorse(Trusted, quote).unwrap();
+
+    let command = shell_command(trusted!("cat"), vec![arg]);
+    assert_eq!(command.into_inner(), "cat 'notes.txt'");
+
+    let input: Int<Untrusted, String> = Int::new("x'; rm -rf ~; '".into());
+    assert_eq!(input.endorse(Trusted, quote), Err("refusing to quote \"x'; rm -rf ~; '\"".to_string()));
+
+    // Does not compile, as intended!
+    // let input: Int<Untrusted, String> = Int::new("notes.txt".into());
+    // shell_command(trusted!("cat"), vec![input]);
+}
+
+#[test]
+fn test_int_weaken() {
+    let flag: Int<Trusted, &str> = trusted!("--verbose");
+    let input: Int<Untrusted, &str> = Int::new("notes.txt");
+
+    let result = flag.weaken(Untrusted).zip_with(input, |f, i| format!("{} {}", f, i));
+    assert_eq!(result.get(), "--verbose notes.txt");
+
+    let result: Int<Trusted, i32> = trusted!(20).map(|i| i + 1).and_then(|i| trusted!(2).map(|j| i * j));
+    assert_eq!(result.into_inner(), 42);
+
+    // Does not compile, as intended!
+    // let input: Int<Untrusted, &str> = Int::new("notes.txt");
+    // input.weaken(Trusted);
+    // let input: Int<Trusted, &str> = Int::new("notes.txt");
+}
+
+#[test]
+fn test_pair_ordering() {
+    let data: Sec<Pair<Low, Trusted>, i32> = Sec::new(42);
+
+    let result: Sec<Pair<Low, Untrusted>, i32> = data.clone().lift(Pair(Low, Untrusted));
+    assert_eq!(result.reveal(&clearance::<Pair<High, Untrusted>>()), 42);
+
+    let result: Sec<Pair<High, Trusted>, i32> = data.clone().lift(Pair(High, Trusted));
+    assert_eq!(result.reveal(&clearance::<Pair<High, Untrusted>>()), 42);
+
+    assert_eq!(data.reveal(&clearance::<Pair<Low, Trusted>>()), 42);
+
+    // Does not compile, as intended!
+    // let data: Sec<Pair<High, Trusted>, i32> = Sec::new(42);
+    // data.reveal(&clearance::<Pair<Low, Trusted>>());
+    // let data: Sec<Pair<Low, Untrusted>, i32> = Sec::new(42);
+    // data.reveal(&clearance::<Pair<High, Trusted>>());
+}
+
+#[test]
+fn test_pair_join() {
+    let name: Sec<Pair<Low, Untrusted>, &str> = Sec::new("Alice");
+    let salary: Sec<Pair<High, Trusted>, u32> = Sec::new(52_000);
+
+    let result: Sec<Pair<High, Untrusted>, String> = name.zip_with(salary, |n, s| format!("{} earns {}", n, s));
+    assert_eq!(result.reveal(&clearance::<Pair<High, Untrusted>>()), "Alice earns 52000");
+}
+
+#[test]
+fn test_pair_debug() {
+    let data: Sec<Pair<High, Trusted>, i32> = Sec::new(42);
+    assert_eq!(format!("{:?}", data), "Sec<Pair<High, Trusted>>(<redacted>)");
+}
+
+#[test]
+fn test_dlm_label_ordering() {
+    use dlm::DlmLabel;
+    use principal::{Principal, PrincipalHierarchy};
+
+    let alice = Principal::new("alice");
+    let bob = Principal::new("bob");
+    let carol = Principal::new("carol");
+
+    let public = DlmLabel::public();
+    let shared = DlmLabel::public().with_policy(&alice, vec![bob.clone(), carol.clone()]);
+    let private = DlmLabel::public().with_policy(&alice, vec![bob.clone()]);
+    let both = private.clone().with_policy(&carol, vec![bob.clone()]);
+
+    assert!(public.can_flow_to(&shared));
+    assert!(shared.can_flow_to(&private));
+    assert!(!private.can_flow_to(&shared));
+    assert!(private.can_flow_to(&both));
+
+    assert_eq!(shared.join(&private), private);
+    assert_eq!(shared.meet(&private), shared);
+    assert_eq!(both.meet(&shared), shared);
+    assert_eq!(format!("{:?}", both), "{alice: bob; carol: bob}");
+
+    // with a hierarchy, a policy owned by someone acting for alice enforces alice's policy too
+    let mut hierarchy = PrincipalHierarchy::new();
+    hierarchy.add_acts_for(&carol, &privilege(&alice));
+    let carols = DlmLabel::public().with_policy(&carol, vec![bob.clone()]);
+
+    assert!(!private.can_flow_to(&carols));
+    assert!(private.can_flow_to_in(&carols, &hierarchy));
+}
+
+#[test]
+fn test_dlm_sec_combinators() {
+    use dlm::{DlmLabel, DlmSec};
+    use principal::{Principal, PrincipalHierarchy};
+
+    let (alice, bob, carol) = (Principal::new("alice"), Principal::new("bob"), Principal::new("carol"));
+    let (as_alice, as_bob, as_carol) = (privilege(&alice), privilege(&bob), privilege(&carol));
+    let hierarchy = PrincipalHierarchy::new();
+
+    let salary: DlmSec<u32> = DlmSec::new(DlmLabel::public().with_policy(&alice, vec![]), 52_000);
+    let bonus: DlmSec<u32> = DlmSec::new(DlmLabel::public().with_policy(&bob, vec![alice.clone()]), 3_000);
+
+    let total = salary.map(|s| s + 1_000).zip_with(bonus, |s, b| s + b);
+    assert_eq!(format!("{:?}", total), "DynSec<{alice: ; bob: alice}>(<redacted>)");
+
+    // bob can't read it, as alice's policy doesn't allow him
+    assert!(total.clone().reveal_to(&as_bob, &hierarchy).is_err());
+    assert_eq!(total.clone().reveal_to(&as_alice, &hierarchy), Ok(56_000));
+
+    // bob's policy can only be declassified by bob
+    let result = total.clone().declassify(&as_alice, &hierarchy, &bob, vec![]);
+    assert!(result.is_err());
+
+    // alice declassifying to carol still leaves bob's policy in place
+    let total = total.declassify(&as_alice, &hierarchy, &alice, vec![bob.clone(), carol.clone()]).unwrap();
+    assert_eq!(total.clone().reveal_to(&as_bob, &hierarchy), Ok(56_000));
+    assert!(total.clone().reveal_to(&as_carol, &hierarchy).is_err());
+
+    let total = total.declassify(&as_bob, &hierarchy, &bob, vec![carol.clone()]).unwrap();
+    assert_eq!(total.reveal_to(&as_carol, &hierarchy), Ok(56_000));
+    assert_eq!(format!("{:?}", as_carol), "Privilege(carol)");
+
+    // Does not compile, as intended!
+    // let total = total.reveal_to(&carol, &hierarchy);
+}
+
+#[test]
+fn test_dlm_acts_for() {
+    use dlm::{DlmLabel, DlmSec};
+    use principal::{Principal, PrincipalHierarchy};
+
+    let alice = Principal::new("alice");
+    let manager = Principal::new("manager");
+    let ceo = privilege(&Principal::new("ceo"));
+
+    // only alice and the manager themselves can let somebody act for them
+    let mut hierarchy = PrincipalHierarchy::new();
+    hierarchy.add_acts_for(ceo.principal(), &privilege(&manager)).add_acts_for(&manager, &privilege(&alice));
+
+    let data: DlmSec<&str> = DlmSec::new(DlmLabel::public().with_policy(&alice, vec![]), "review");
+    assert_eq!(data.clone().reveal_to(&ceo, &hierarchy), Ok("review"));
+
+    let result = data.clone().declassify(&ceo, &hierarchy, &alice, vec![manager.clone()]);
+    assert!(result.is_ok());
+
+    hierarchy.remove_acts_for(&manager, &alice);
+    assert!(!hierarchy.acts_for(ceo.principal(), &alice));
+    assert!(data.reveal_to(&ceo, &hierarchy).is_err());
+}
+
+#[test]
+fn test_dc_component_normalization() {
+    use dc_label::Component;
+    use principal::Principal;
+
+    let alice = Component::principal(&Principal::new("alice"));
+    let bob = Component::principal(&Principal::new("bob"));
+    let carol = Component::principal(&Principal::new("carol"));
+
+    // (alice \/ bob) /\ alice == alice
+    assert_eq!(alice.or(&bob).and(&alice), alice);
+    // alice \/ (alice /\ bob) == alice
+    assert_eq!(alice.or(&alice.and(&bob)), alice);
+    assert_eq!(alice.and(&Component::always_true()), alice);
+    assert_eq!(alice.or(&Component::always_false()), alice);
+    assert!(alice.or(&Component::always_true()).is_true());
+    assert!(alice.and(&Component::always_false()).is_false());
+    assert!(Component::from_clauses(vec![Vec::new()]).is_false());
+
+    let formula = alice.or(&bob.and(&carol));
+    assert_eq!(formula, Component::from_clauses(vec![
+        vec![Principal::new("carol"), Principal::new("alice")],
+        vec![Principal::new("bob"), Principal::new("alice")],
+    ]));
+    assert_eq!(format!("{:?}", formula), "(alice \\/ bob) /\\ (alice \\/ carol)");
+
+    assert!(alice.implies(&formula));
+    assert!(bob.and(&carol).implies(&formula));
+    assert!(!carol.implies(&formula));
+    assert!(Component::always_false().implies(&alice));
+    assert!(alice.implies(&Component::always_true()));
+    assert!(!Component::always_true().implies(&alice));
+}
+
+#[test]
+fn test_dc_label_lattice() {
+    use dc_label::{Component, DCLabel, DCSec};
+    use principal::Principal;
+
+    let alice = Component::principal(&Principal::new("alice"));
+    let bob = Component::principal(&Principal::new("bob"));
+    let tru = Component::always_true();
+
+    let alices = DCLabel::new(alice.clone(), tru.clone());
+    let bobs = DCLabel::new(bob.clone(), tru.clone());
+    let both = alices.join(&bobs);
+
+    assert_eq!(both, DCLabel::new(alice.and(&bob), tru.clone()));
+    assert_eq!(alices.meet(&bobs), DCLabel::new(alice.or(&bob), tru.clone()));
+    assert!(alices.can_flow_to(&both));
+    assert!(!both.can_flow_to(&alices));
+    assert!(!alices.can_flow_to(&bobs));
+    assert!(DCLabel::public().can_flow_to(&alices));
+
+    // integrity goes the other way: data vouched for by alice may flow where no one vouches for it
+    let endorsed = DCLabel::new(tru.clone(), alice.clone());
+    assert!(endorsed.can_flow_to(&DCLabel::public()));
+    assert!(!DCLabel::public().can_flow_to(&endorsed));
+
+    for label in &[alices.clone(), bobs.clone(), both.clone(), endorsed.clone()] {
+        assert!(DCLabel::bottom().can_flow_to(label));
+        assert!(label.can_flow_to(&DCLabel::top()));
+    }
+
+    let x: DCSec<i32> = DCSec::new(alices, 20);
+    let y: DCSec<i32> = DCSec::new(endorsed, 22);
+    let result = x.zip_with(y, |a, b| a + b);
+    assert_eq!(format!("{:?}", result), "DynSec<<alice, True>>(<redacted>)");
+    assert_eq!(result.reveal(&dyn_clearance(DCLabel::new(alice, tru))), Ok(42));
+}
+
+#[test]
+fn test_noninterference_holds() {
+    use testing::Noninterference;
+
+    // only touches the secret through `map`, so nothing can leak
+    let bonus = |salary: Sec<High, u32>, percent: u8| {
+        let bonus = salary.map(|s| s as u64 * percent as u64 / 100);
+        (format!("{}% bonus", percent), bonus.labeled_eq(&Sec::new(0)))
+    };
+
+    assert!(Noninterference::new().check(|salary, percent| bonus(salary, percent).0).is_ok());
+    Noninterference::new().cases(64).seed(7).assert(|names: Sec<High, Vec<String>>, n: usize| {
+        let _ = names.map(|names| names.len() + n);
+        n.wrapping_mul(2)
+    });
+}
+
+#[test]
+fn test_noninterference_catches_reveal() {
+    use testing::{Noninterference, Outcome};
+
+    let counterexample = Noninterference::new()
+        .check(|secret: Sec<High, i32>, threshold: i32| secret.reveal(HIGH) > threshold)
+        .unwrap_err();
+
+    // shrunk until the secrets are right next to the threshold, on either side of it
+    let threshold = counterexample.low;
+    let (a, b) = counterexample.high;
+    assert_eq!((a.min(b), a.max(b)), (threshold, threshold + 1));
+    assert_eq!(counterexample.outcomes.0, Outcome::Returned(a > threshold));
+}
+
+#[test]
+fn test_noninterference_catches_panics() {
+    use testing::{Noninterference, Outcome};
+
+    let counterexample = Noninterference::new()
+        .check(|secret: Sec<High, Vec<u8>>, index: usize| {
+            let secret = secret.reveal(HIGH);
+            // panics when the index is out of bounds, which depends on the length of the secret
+            secret[index % 4] > 0 || index > 0
+        })
+        .unwrap_err();
+
+    let panicked = |outcome: &Outcome<bool>| match *outcome {
+        Outcome::Panicked(ref message) => message.contains("out of bounds"),
+        Outcome::Returned(_) => false,
+    };
+    assert!(panicked(&counterexample.outcomes.0) != panicked(&counterexample.outcomes.1));
+    assert!(counterexample.high.0.len() + counterexample.high.1.len() <= 1);
+}
+
+#[test]
+fn test_noninterference_reports_counterexample() {
+    use std::panic;
+    use testing::Noninterference;
+
+    let result = panic::catch_unwind(|| {
+        Noninterference::new().assert(|secret: Sec<High, bool>, _: ()| secret.reveal(HIGH))
+    });
+    let message = *result.unwrap_err().downcast::<String>().unwrap();
+
+    assert!(message.starts_with("noninterference violated for the public input ():"), "{}", message);
+    assert!(message.contains("gives Returned(true)"));
+    assert!(message.contains("gives Returned(false)"));
+}
+
+#[test]
+fn test_clearance_lowering() {
+    use dynamic::{FlowError, Level};
+
+    let high = clearance::<High>();
+    let low = high.lower::<Low>();
+
+    assert_eq!(format!("{:?} {:?}", high, low), "Clearance<High> Clearance<Low>");
+    assert_eq!(Sec::<Low, i32>::new(1).reveal(&low), 1);
+    assert_eq!(Sec::<Pair<Low, Trusted>, i32>::new(2).reveal(&clearance::<Pair<High, Untrusted>>()), 2);
+
+    let dynamic = high.to_dynamic::<Level>();
+    assert_eq!(dynamic.label(), &Level::High);
+    assert_eq!(format!("{:?}", dynamic), "DynClearance<High>");
+    assert_eq!(DynSec::new(Level::Low, 3).reveal(&dynamic.lower(Level::Low).unwrap()), Ok(3));
+
+    let error = dyn_clearance(Level::Low).lower(Level::High).unwrap_err();
+    assert_eq!(error, FlowError { from: Level::High, to: Level::Low });
+
+    // Does not compile, as intended!
+    // low.lower::<High>();
+    // Sec::<High, i32>::new(4).reveal(High);
+}
+
+#[test]
+fn test_sec_ref_read_write() {
+    use sec_ref::SecRef;
+
+    let cell: SecRef<High, Vec<i32>> = SecRef::new(vec![1]);
+    let alias = cell.clone();
+
+    // writes from below, and from the same level, are both seen through every alias
+    alias.write(Sec::<Low, Vec<i32>>::new(vec![2]));
+    assert_eq!(cell.read().reveal(HIGH), vec![2]);
+
+    cell.update(Sec::<High, i32>::new(3), |v, i| v.push(i));
+    assert_eq!(alias.read_with(|v| v.iter().sum::<i32>()).reveal(HIGH), 5);
+
+    let cell: SecRef<High, i32> = SecRef::from_sec(Sec::<Low, i32>::new(6));
+    assert_eq!(cell.read().reveal(HIGH), 6);
+    assert_eq!(format!("{:?}", cell), "SecRef<High>(<redacted>)");
+
+    // Does not compile, as intended!
+    // let public: SecRef<Low, i32> = SecRef::new(0);
+    // public.write(Sec::<High, i32>::new(7));
+    // let leaked: Sec<Low, i32> = cell.read();
+}
+
+#[test]
+fn test_sync_sec_ref_threads() {
+    use std::collections::HashMap;
+    use std::{panic, thread};
+    use sec_ref::SyncSecRef;
+
+    let sessions: SyncSecRef<High, HashMap<u32, String>> = SyncSecRef::new(HashMap::new());
+
+    let handles: Vec<_> = (0..8).map(|id| {
+        let sessions = sessions.clone();
+        thread::spawn(move || {
+            let token: Sec<Low, String> = Sec::new(format!("token-{}", id));
+            sessions.update(token, |map, token| {
+                map.insert(id, token);
+            });
+        })
+    }).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(sessions.read_with(|map| map.len()).reveal(HIGH), 8);
+    assert_eq!(sessions.read().reveal(HIGH)[&3], "token-3");
+
+    // a panic while updating doesn't poison the reference
+    let alias = sessions.clone();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        alias.update(Sec::<High, ()>::new(()), |map, _| {
+            map.clear();
+            panic!("interrupted");
+        })
+    }));
+    assert!(result.is_err());
+
+    sessions.write(Sec::<High, HashMap<u32, String>>::new(HashMap::new()));
+    assert!(sessions.read_with(HashMap::is_empty).reveal(HIGH));
+    assert_eq!(format!("{:?}", sessions), "SyncSecRef<High>(<redacted>)");
+}
+
+#[test]
+fn test_sec_channel_between_threads() {
+    use std::sync::mpsc::TryRecvError;
+    use std::thread;
+    use channel::sec_channel;
+
+    let (sender, receiver) = sec_channel::<High, u64>();
+
+    let workers: Vec<_> = (1..=4u64).map(|i| {
+        let sender = sender.clone();
+        thread::spawn(move || {
+            // the workers only ever see their inputs wrapped up
+            let input: Sec<Low, u64> = Sec::new(i);
+            sender.send(input.map(|i| i * i)).unwrap();
+        })
+    }).collect();
+    drop(sender);
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    let squares: Sec<High, Vec<u64>> = receiver.iter().collect();
+    let mut squares = squares.reveal(HIGH);
+    squares.sort();
+    assert_eq!(squares, vec![1, 4, 9, 16]);
+    assert_eq!(receiver.try_recv().unwrap_err(), TryRecvError::Disconnected);
+
+    // a send to a dropped receiver gives the data back, at the channel's level
+    let (sender, receiver) = sec_channel::<High, u64>();
+    drop(receiver);
+    let error = sender.send(Sec::<Low, u64>::new(5)).unwrap_err();
+    assert_eq!(error.0.reveal(HIGH), 5);
+    assert_eq!(format!("{:?}", sender), "SecSender<High>(..)");
+
+    // Does not compile, as intended!
+    // let (sender, _) = sec_channel::<Low, u64>();
+    // sender.send(Sec::<High, u64>::new(6));
+}
+
+#[test]
+fn test_sync_sec_channel_bounded() {
+    use std::sync::mpsc::{RecvTimeoutError, TrySendError};
+    use std::thread;
+    use std::time::Duration;
+    use channel::sync_sec_channel;
+
+    let (sender, receiver) = sync_sec_channel::<High, i32>(2);
+    sender.send(Sec::<High, i32>::new(1)).unwrap();
+    sender.try_send(Sec::<Low, i32>::new(2)).unwrap();
+
+    match sender.try_send(Sec::<Low, i32>::new(3)) {
+        Err(TrySendError::Full(data)) => assert_eq!(data.reveal(HIGH), 3),
+        _ => panic!("expected the buffer to be full"),
+    }
+
+    // a blocked send goes through once there's room
+    let blocked = thread::spawn(move || sender.send(Sec::<High, i32>::new(4)).is_ok());
+    assert_eq!(receiver.recv().unwrap().reveal(HIGH), 1);
+    assert!(blocked.join().unwrap());
+
+    let rest: Vec<i32> = receiver.iter().map(|data| data.reveal(HIGH)).collect();
+    assert_eq!(rest, vec![2, 4]);
+    assert_eq!(receiver.recv_timeout(Duration::from_millis(1)).unwrap_err(), RecvTimeoutError::Disconnected);
+}
+
+#[test]
+fn test_sec_select_many_receivers() {
+    use std::sync::mpsc::{RecvTimeoutError, TryRecvError};
+    use std::thread;
+    use std::time::Duration;
+    use channel::{sec_channel, sync_sec_channel, SecSelect};
+
+    let (low, low_receiver) = sec_channel::<Low, String>();
+    let (high, high_receiver) = sync_sec_channel::<High, String>(0);
+
+    let mut select = SecSelect::<High, String>::new();
+    assert_eq!(select.add(&low_receiver), 0);
+    assert_eq!(select.add(&high_receiver), 1);
+    assert_eq!(format!("{:?}", select), "SecSelect<High>(2 receivers)");
+
+    assert_eq!(select.try_recv().unwrap_err(), TryRecvError::Empty);
+    assert_eq!(select.recv_timeout(Duration::from_millis(5)).unwrap_err(), RecvTimeoutError::Timeout);
+
+    low.send(Sec::<Low, String>::new("public".into())).unwrap();
+    let sender = thread::spawn(move || high.send(Sec::<High, String>::new("secret".into())).unwrap());
+
+    let mut received: Vec<(usize, String)> = (0..2)
+        .map(|_| select.recv().unwrap())
+        .map(|(index, message)| (index, message.reveal(HIGH)))
+        .collect();
+    received.sort();
+    assert_eq!(received, vec![(0, "public".to_string()), (1, "secret".to_string())]);
+
+    sender.join().unwrap();
+    drop(low);
+    assert!(select.recv().is_err());
+    assert_eq!(SecSelect::<Low, ()>::new().try_recv().unwrap_err(), TryRecvError::Disconnected);
+
+    // Does not compile, as intended!
+    // let mut select = SecSelect::<Low, String>::new();
+    // select.add(&high_receiver);
+}
+
+#[test]
+fn test_sec_thread_join() {
+    use sec_thread::{self, SecPanic};
+
+    let secret: Sec<High, Vec<u32>> = Sec::new(vec![3, 1, 2]);
+    let sorted = secret.spawn_map(|mut v| {
+        v.sort();
+        v
+    });
+    assert_eq!(sorted.join().reveal(HIGH), Ok(vec![1, 2, 3]));
+
+    let public: Sec<Low, u32> = Sec::new(20);
+    let handle = public.spawn_and_then(|i| Sec::<Low, u32>::new(i + 22));
+    assert_eq!(format!("{:?}", handle), "SecJoinHandle<Low>(..)");
+    assert_eq!(handle.join().reveal(LOW), Ok(42));
+
+    // the panic is only visible with clearance
+    let secret: Sec<High, u32> = Sec::new(0);
+    let failed = sec_thread::spawn(move || secret.map(|i| 100 / i)).join();
+    assert_eq!(format!("{:?}", failed), "Sec<High>(<redacted>)");
+
+    let error: SecPanic = failed.reveal(HIGH).unwrap_err();
+    assert!(error.message().contains("divide by zero"));
+    assert!(error.to_string().starts_with("labeled thread panicked: "));
+
+    // Does not compile, as intended!
+    // let leaked: Sec<Low, Result<u32, SecPanic>> = sec_thread::spawn(|| Sec::<High, u32>::new(1)).join();
+}
+
+#[test]
+fn test_zeroize_leaves_collections_empty() {
+    use zeroize::{Zeroize, Zeroizing};
+
+    let mut key = vec![1u8, 2, 3];
+    key.zeroize();
+    assert!(key.is_empty());
+
+    let mut pin = Some([1u32, 2, 3, 4]);
+    pin.zeroize();
+    assert_eq!(pin, None);
+
+    let mut name = String::from("alice");
+    name.zeroize();
+    assert_eq!(name, "");
+
+    let mut buffer = Zeroizing::new(vec![1u8; 4].into_boxed_slice());
+    buffer[0] = 9;
+    assert_eq!(format!("{:?}", buffer), "Zeroizing(<redacted>)");
+    buffer.zeroize();
+    assert_eq!(*buffer, vec![0u8; 4].into_boxed_slice());
+
+    let secret = Sec::<High, _>::new_zeroizing(String::from("hunter2"));
+    assert_eq!(format!("{:?}", secret.map_zeroizing(|p| p.len())), "Sec<High>(<redacted>)");
+}
+
+#[test]
+fn test_constant_time_integers() {
+    use constant_time::{Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeLess};
+
+    let mut rng = XorShift(0x1234_5678);
+    let samples: Vec<(u64, u64)> = (0..1000).map(|_| (rng.next(), rng.next() % 4)).collect();
+
+    // agrees with the branching operators, on random values as well as the edges of the ranges
+    for &(a, b) in samples.iter().chain(&[(0, 0), (0, u64::MAX), (u64::MAX, 0), (u64::MAX, u64::MAX)]) {
+        assert_eq!(bool::from(a.ct_eq(&b)), a == b);
+        assert_eq!(bool::from(a.ct_lt(&b)), a < b, "{} < {}", a, b);
+        assert_eq!(bool::from((a as u8).ct_lt(&(b as u8))), (a as u8) < (b as u8));
+
+        let (x, y) = (a as i32, b as i32 - 2);
+        assert_eq!(bool::from(x.ct_lt(&y)), x < y, "{} < {}", x, y);
+        assert_eq!(bool::from(y.ct_lt(&x)), y < x, "{} < {}", y, x);
+    }
+    assert!(bool::from(i64::MIN.ct_lt(&i64::MAX)));
+    assert!(!bool::from(i64::MAX.ct_lt(&i64::MIN)));
+
+    assert_eq!(u32::ct_select(&1, &2, Choice::from(false)), 1);
+    assert_eq!(i16::ct_select(&1, &-2, Choice::from(true)), -2);
+
+    let yes = Choice::from(true);
+    let no = Choice::from(false);
+    assert_eq!(((yes & no).unwrap_u8(), (yes | no).unwrap_u8(), (!yes).unwrap_u8()), (0, 1, 0));
+}
+
+#[test]
+fn test_constant_time_bytes() {
+    use constant_time::Choice;
+
+    let token: Sec<High, Vec<u8>> = Sec::new(b"0123456789abcdef".to_vec());
+    let same: Sec<High, Vec<u8>> = Sec::new(b"0123456789abcdef".to_vec());
+    let last_differs: Sec<High, Vec<u8>> = Sec::new(b"0123456789abcdeF".to_vec());
+    let shorter: Sec<High, Vec<u8>> = Sec::new(b"0123".to_vec());
+
+    let check = |choice: Sec<High, Choice>| choice.map(bool::from).reveal(HIGH);
+    assert!(check(token.ct_eq(&same)));
+    assert!(!check(token.ct_eq(&last_differs)));
+    assert!(!check(token.ct_eq(&shorter)));
+
+    // lexicographic, like the ordering of slices
+    assert!(check(last_differs.ct_lt(&token)));
+    assert!(!check(token.ct_lt(&last_differs)));
+    assert!(!check(token.ct_lt(&same)));
+    assert!(check(shorter.ct_lt(&token)));
+    assert!(!check(token.ct_lt(&shorter)));
+
+    let picked = token.ct_select(&last_differs, &token.ct_eq(&shorter));
+    assert_eq!(picked.reveal(HIGH), b"0123456789abcdef");
+
+    let keys: Sec<Low, [u8; 4]> = Sec::new([1, 2, 3, 4]);
+    let other: Sec<Low, [u8; 4]> = Sec::new([5, 6, 7, 8]);
+    let picked = keys.ct_select(&other, &keys.ct_lt(&other));
+    assert_eq!(picked.reveal(LOW), [5, 6, 7, 8]);
+
+    // Does not compile, as intended!
+    // let leaked: bool = token.ct_eq(&same).into();
+}
+
+#[test]
+fn test_map_padded_fixed_deadline() {
+    use std::panic;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    let deadline = Duration::from_millis(30);
+    let work = |secret: u64| thread::sleep(Duration::from_millis(secret));
+
+    // quick and slow secrets alike take the whole deadline
+    for &secret in &[0, 2, 10] {
+        let start = Instant::now();
+        let result = Sec::<High, u64>::new(secret).map_padded(deadline, |s| {
+            work(s);
+            s * 2
+        });
+        assert!(start.elapsed() >= deadline);
+        assert_eq!(result.reveal(HIGH), secret * 2);
+    }
+
+    // an overrun isn't cut short
+    let start = Instant::now();
+    Sec::<High, u64>::new(40).map_padded(deadline, work);
+    assert!(start.elapsed() >= Duration::from_millis(40));
+
+    // neither is a panic let out early
+    let start = Instant::now();
+    let result = panic::catch_unwind(|| Sec::<High, u64>::new(0).map_padded(deadline, |_| panic!("failed")));
+    assert!(result.is_err());
+    assert!(start.elapsed() >= deadline);
+}
+
+#[test]
+fn test_map_padded_predictive() {
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use timing::{PaddingPolicy, Predictive};
+
+    let policy = Predictive::new(Duration::from_millis(4));
+    assert_eq!((policy.quantum(), policy.epochs()), (Duration::from_millis(4), 0));
+
+    let start = Instant::now();
+    Sec::<High, ()>::new(()).map_padded(&policy, |_| ());
+    assert!(start.elapsed() >= Duration::from_millis(4));
+
+    // a misprediction doubles the quantum, until it covers the computation
+    let start = Instant::now();
+    Sec::<High, ()>::new(()).map_padded(&policy, |_| thread::sleep(Duration::from_millis(10)));
+    let (quantum, epochs) = (policy.quantum(), policy.epochs());
+    assert!(quantum >= Duration::from_millis(16) && epochs >= 2);
+    assert_eq!(quantum, Duration::from_millis(4) * (1 << epochs));
+    assert!(start.elapsed() >= quantum);
+
+    // and quick computations are padded to the new quantum from then on
+    let start = Instant::now();
+    Sec::<High, ()>::new(()).map_padded(&policy, |_| ());
+    assert!(start.elapsed() >= quantum);
+    assert_eq!(policy.epochs(), epochs);
+
+    assert_eq!(policy.padded(Duration::from_millis(1)), quantum);
+    assert_eq!(Duration::from_millis(5).padded(Duration::from_millis(1)), Duration::from_millis(5));
+    assert_eq!(Duration::from_millis(5).padded(Duration::from_millis(7)), Duration::from_millis(7));
+}
+
+#[test]
+fn test_fallible_chains_keep_errors_labeled() {
+    use fallible::SecError;
+
+    fn withdraw(balance: u32, amount: u32) -> Sec<High, Result<u32, String>> {
+        Sec::new(balance.checked_sub(amount).ok_or(format!("balance {} too low", balance)))
+    }
+
+    let balance: Sec<High, &str> = Sec::new("100");
+    let parsed = balance.map(|b| b.parse::<u32>().map_err(|e| e.to_string()));
+    let remaining = parsed.clone().try_and_then(|b| withdraw(b, 30)).try_map(|b| Ok(b * 2));
+    assert_eq!(remaining.reveal(HIGH), Ok(140));
+
+    // the first error short-circuits the rest of the chain
+    let overdrawn = parsed.try_and_then(|b| withdraw(b, 130)).try_map(|_| -> Result<u32, String> { unreachable!() });
+    assert_eq!(format!("{:?}", overdrawn), "Sec<High>(<redacted>)");
+
+    let error: SecError<High, usize> = overdrawn.map_err(|e| e.len()).into_result(HIGH).unwrap_err();
+    assert_eq!(format!("{:?}", error), "SecError<High>(<redacted>)");
+    assert_eq!(format!("{}", error.unredacted(HIGH)), "19");
+    assert_eq!(error.into_sec().reveal(HIGH), 19);
+
+    // `?` propagates the labeled error unchanged
+    fn parse(input: Sec<Low, &str>) -> Result<Sec<Low, i64>, SecError<Low, std::num::ParseIntError>> {
+        let number = input.map(|i| i.parse::<i64>()).into_result(LOW)?;
+        Ok(number.map(|n| -n))
+    }
+    assert_eq!(parse(Sec::new("42")).unwrap().reveal(LOW), -42);
+    assert_eq!(parse(Sec::new("forty-two")).unwrap_err().to_string(), "SecError<Low>(<redacted>)");
+
+    // Does not compile, as intended!
+    // let leaked: Result<Sec<High, u32>, SecError<High, String>> = remaining.into_result(LOW);
+}
+
+mod lattices {
+    use super::super::prelude::*;
+    use super::clearance;
+    use security_lattice;
+
+    security_lattice! {
+        pub Public < Internal < Secret < TopSecret
+    }
+
+    security_lattice! {
+        pub Staff;
+        pub Hr > Staff;
+        pub Finance > Staff;
+        pub Board > Hr, Finance;
+    }
+
+    #[test]
+    fn test_chain_lattice() {
+        // reflexive
+        let data: Sec<Secret, i32> = 1.into();
+        assert_eq!(data.reveal(&clearance::<Secret>()), 1);
+
+        // direct
+        let data: Sec<Public, i32> = 2.into();
+        assert_eq!(data.lift(Internal).reveal(&clearance::<Internal>()), 2);
+
+        // transitive
+        let data: Sec<Public, i32> = 3.into();
+        assert_eq!(data.lift(TopSecret).reveal(&clearance::<TopSecret>()), 3);
+
+        let data: Sec<Internal, i32> = 4.into();
+        assert_eq!(data.reveal(&clearance::<TopSecret>()), 4);
+
+        // Does not compile, as intended!
+        // let data: Sec<TopSecret, i32> = 5.into();
+        // data.reveal(&clearance::<Secret>());
+    }
+
+    #[test]
+    fn test_diamond_lattice() {
+        let data: Sec<Staff, i32> = 1.into();
+        assert_eq!(data.lift(Hr).lift(Board).reveal(&clearance::<Board>()), 1);
+
+        let data: Sec<Staff, i32> = 2.into();
+        assert_eq!(data.reveal(&clearance::<Finance>()), 2);
+
+        let data: Sec<Finance, i32> = 3.into();
+        assert_eq!(data.reveal(&clearance::<Board>()), 3);
+
+        assert_eq!(format!("{:?}", Sec::<Hr, i32>::new(4)), "Sec<Hr>(<redacted>)");
+
+        // Does not compile, as intended!
+        // let data: Sec<Hr, i32> = 5.into();
+        // data.reveal(&clearance::<Finance>());
+    }
+
+    #[test]
+    fn test_lattice_join() {
+        let a: Sec<Internal, i32> = 1.into();
+        let b: Sec<Secret, i32> = 2.into();
+        let result: Sec<Secret, i32> = a.zip_with(b, |a, b| a + b);
+
+        assert_eq!(result.reveal(&clearance::<Secret>()), 3);
+
+        // incomparable levels join at the top of the diamond
+        let a: Sec<Hr, i32> = 3.into();
+        let b: Sec<Finance, i32> = 4.into();
+        let result: Sec<Board, i32> = a.zip_with(b, |a, b| a + b);
+
+        assert_eq!(result.reveal(&clearance::<Board>()), 7);
+
+        let a: Sec<Staff, i32> = 5.into();
+        let b: Sec<Hr, i32> = 6.into();
+        let result: Sec<Hr, i32> = a.zip_with(b, |a, b| a + b);
+
+        assert_eq!(result.reveal(&clearance::<Hr>()), 11);
+
+        let a: Sec<Board, i32> = 7.into();
+        let b: Sec<Board, i32> = 8.into();
+        let result: Sec<Board, i32> = a.zip_with(b, |a, b| a + b);
+
+        assert_eq!(result.reveal(&clearance::<Board>()), 15);
+    }
+
+    #[test]
+    fn test_lattice_meet() {
+        fn lower<A, S1, S2>(data: A, _: S1, _: S2) -> Sec<MeetOf<S1, S2>, A>
+        where
+            S1: Meet<S2>,
+            S2: SecurityLevel,
+        {
+            Sec::new(data)
+        }
+
+        let result: Sec<Staff, i32> = lower(1, Hr, Finance);
+        assert_eq!(result.reveal(&clearance::<Staff>()), 1);
+
+        let result: Sec<Hr, i32> = lower(2, Board, Hr);
+        assert_eq!(result.reveal(&clearance::<Hr>()), 2);
+
+        let result: Sec<Internal, i32> = lower(3, TopSecret, Internal);
+        assert_eq!(result.reveal(&clearance::<Internal>()), 3);
+
+        let result: Sec<Public, i32> = lower(4, Public, Public);
+        assert_eq!(result.reveal(&clearance::<Public>()), 4);
+    }
+}
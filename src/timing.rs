@@ -0,0 +1,160 @@
+//! Timing-channel mitigation for computations over `Sec`.
+//!
+//! Labels keep a secret's value from flowing anywhere it shouldn't, but not how long it takes to compute with it:
+//! a low observer timing a response derived from `High` data learns something about that data.
+//! `Sec::map_padded` closes that channel by padding the computation out to a duration chosen by a `PaddingPolicy`,
+//! so that (as long as the policy's prediction holds) the time taken is independent of the secret.
+//!
+//! Two policies are provided:
+//!
+//! * A `Duration` is a fixed deadline. Computations that overrun it are not cut short, so an overrun is visible.
+//! * `Predictive` implements predictive mitigation with doubling epochs, after Askarov, Zhang and Myers,
+//!   "Predictive Black-Box Mitigation of Timing Channels". Every computation is padded to the current quantum;
+//!   one that overruns it doubles the quantum for it and everything after. As the quantum only ever doubles,
+//!   at most logarithmically many bits can leak through the epoch changes, however many computations are run.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::Sec;
+use super::security_level as sl;
+
+/// Decides how long a computation should appear to take.
+pub trait PaddingPolicy {
+    /// Returns how long a computation that actually took `elapsed` should be padded to, which is at least `elapsed`.
+    fn padded(&self, elapsed: Duration) -> Duration;
+}
+
+/// A fixed deadline.
+impl PaddingPolicy for Duration {
+    fn padded(&self, elapsed: Duration) -> Duration {
+        elapsed.max(*self)
+    }
+}
+
+impl<P> PaddingPolicy for &P
+where
+    P: PaddingPolicy + ?Sized,
+{
+    fn padded(&self, elapsed: Duration) -> Duration {
+        (**self).padded(elapsed)
+    }
+}
+
+/// Predictive mitigation with doubling epochs.
+///
+/// It can be shared between threads, so a whole service can run on a single schedule.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use seclib::timing::{PaddingPolicy, Predictive};
+///
+/// let policy = Predictive::new(Duration::from_millis(10));
+/// assert_eq!(policy.padded(Duration::from_millis(3)), Duration::from_millis(10));
+///
+/// // a misprediction starts a new epoch, with twice the quantum, or more if need be
+/// assert_eq!(policy.padded(Duration::from_millis(25)), Duration::from_millis(40));
+/// assert_eq!(policy.padded(Duration::from_millis(3)), Duration::from_millis(40));
+/// assert_eq!(policy.epochs(), 2);
+/// ```
+#[derive(Debug)]
+pub struct Predictive {
+    // the current quantum, in nanoseconds
+    quantum: AtomicU64,
+    epochs: AtomicUsize,
+}
+
+impl Predictive {
+    /// Constructor. Starts out predicting that every computation takes at most `initial`.
+    ///
+    /// # Panics
+    /// When `initial` is zero, as doubling it would get nowhere.
+    pub fn new(initial: Duration) -> Self {
+        assert!(initial > Duration::from_nanos(0), "the initial quantum must not be zero");
+        Predictive { quantum: AtomicU64::new(initial.as_nanos() as u64), epochs: AtomicUsize::new(0) }
+    }
+
+    /// The current quantum, which computations are padded to.
+    pub fn quantum(&self) -> Duration {
+        Duration::from_nanos(self.quantum.load(Ordering::SeqCst))
+    }
+
+    /// How many times the quantum has been doubled so far.
+    pub fn epochs(&self) -> usize {
+        self.epochs.load(Ordering::SeqCst)
+    }
+}
+
+impl PaddingPolicy for Predictive {
+    fn padded(&self, elapsed: Duration) -> Duration {
+        let elapsed = elapsed.as_nanos() as u64;
+        let (mut padded, mut doublings) = (0, 0);
+        // retried if another thread moved the quantum on in the meantime
+        let _ = self.quantum.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |mut quantum| {
+            doublings = 0;
+            while quantum < elapsed {
+                quantum = quantum.saturating_mul(2);
+                doublings += 1;
+            }
+            padded = quantum;
+            Some(quantum)
+        });
+
+        self.epochs.fetch_add(doublings, Ordering::SeqCst);
+        Duration::from_nanos(padded)
+    }
+}
+
+// Sleeps until as long as the policy says has passed since `start`
+fn pad<P>(start: Instant, policy: &P)
+where
+    P: PaddingPolicy,
+{
+    let target = policy.padded(start.elapsed());
+    loop {
+        let elapsed = start.elapsed();
+        if elapsed >= target {
+            break;
+        }
+        thread::sleep(target - elapsed);
+    }
+}
+
+impl<S, A> Sec<S, A>
+where
+    S: sl::SecurityLevel,
+{
+    /// Like `map`, but always takes at least as long as `policy` says, whatever the data.
+    ///
+    /// A panic in `f` is padded just the same, before it's resumed.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let password: Sec<High, &str> = Sec::new("hunter2");
+    ///
+    /// let start = Instant::now();
+    /// let valid = password.map_padded(Duration::from_millis(20), |p| p == "correct horse battery staple");
+    ///
+    /// assert!(start.elapsed() >= Duration::from_millis(20));
+    /// assert_eq!(valid.reveal(&high), false);
+    /// ```
+    pub fn map_padded<B, P, F>(self, policy: P, f: F) -> Sec<S, B>
+    where
+        P: PaddingPolicy,
+        F: FnOnce(A) -> B,
+    {
+        let start = Instant::now();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| self.map(f)));
+        pad(start, &policy);
+
+        result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+    }
+}
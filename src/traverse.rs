@@ -0,0 +1,134 @@
+//! Collections of `Sec` values.
+//!
+//! A collection of `Sec<S, A>` can be turned into a single `Sec<S, _>` of the collection, without revealing anything,
+//! using `sequence`, `traverse`, or `collect`. The level always stays `S`.
+//!
+//! The same goes for pulling a `Sec` out of an `Option` or `Result`.
+//! The other way around tells whether there was a value (or an error) inside the `Sec`,
+//! so like `reveal`, it must be supplied with a `Clearance` for a security level &geq; the `Sec`'s.
+
+use std::iter::FromIterator;
+
+use super::Sec;
+use super::clearance::Clearance;
+use super::security_level as sl;
+
+impl<S, A> Sec<S, Vec<A>>
+where
+    S: sl::SecurityLevel,
+{
+    /// Turns a collection of `Sec`s into a `Sec` of a `Vec`.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let data: Vec<Sec<High, i32>> = vec![1.into(), 2.into(), 3.into()];
+    /// let result: Sec<High, Vec<i32>> = Sec::sequence(data);
+    ///
+    /// assert_eq!(result.reveal(&high), vec![1, 2, 3]);
+    /// ```
+    pub fn sequence<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Sec<S, A>>,
+    {
+        Sec::new(iter.into_iter().map(|sec| sec.data).collect())
+    }
+
+    /// Maps a function returning a `Sec` over a collection, and sequences the results.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// fn lookup(user: &str) -> Sec<High, usize> {
+    ///     Sec::new(user.len())
+    /// }
+    ///
+    /// let result = Sec::traverse(vec!["alice", "bob"], lookup);
+    ///
+    /// assert_eq!(result.reveal(&high), vec![5, 3]);
+    /// ```
+    pub fn traverse<I, F>(iter: I, mut f: F) -> Self
+    where
+        I: IntoIterator,
+        F: FnMut(I::Item) -> Sec<S, A>,
+    {
+        Sec::new(iter.into_iter().map(|item| f(item).data).collect())
+    }
+}
+
+/// Collects `Sec`s into a `Sec` of any collection.
+impl<S, A, C> FromIterator<Sec<S, A>> for Sec<S, C>
+where
+    S: sl::SecurityLevel,
+    C: FromIterator<A>,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Sec<S, A>>,
+    {
+        Sec::new(iter.into_iter().map(|sec| sec.data).collect())
+    }
+}
+
+impl<S, A> Sec<S, Option<A>>
+where
+    S: sl::SecurityLevel,
+{
+    /// Moves the `Option` into the `Sec`.
+    pub fn from_option(option: Option<Sec<S, A>>) -> Self {
+        Sec::new(option.map(|sec| sec.data))
+    }
+
+    /// Moves the `Option` out of the `Sec`, revealing whether there's a value.
+    /// Like `reveal`, it must be supplied with a `Clearance` for a security level &geq; the `Sec`'s.
+    ///
+    /// # Examples
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let high = authority.clearance::<High>();
+    /// let data: Sec<High, Option<i32>> = Sec::new(Some(12));
+    ///
+    /// assert_eq!(data.transpose(&high).map(|sec| sec.reveal(&high)), Some(12));
+    /// ```
+    /// ```compile_fail
+    /// use seclib::prelude::*;
+    ///
+    /// let authority = Authority::acquire().unwrap();
+    /// let data: Sec<High, Option<i32>> = Sec::new(Some(12));
+    ///
+    /// data.transpose(&authority.clearance::<Low>()).is_some(); // ERROR: the trait `SecurityLevel<High>` is not implemented for `Low`
+    /// ```
+    pub fn transpose<S2>(self, _: &Clearance<S2>) -> Option<Sec<S, A>>
+    where
+        S2: sl::SecurityLevel<S> + sl::SecurityLevel,
+    {
+        self.data.map(Sec::new)
+    }
+}
+
+impl<S, A, E> Sec<S, Result<A, E>>
+where
+    S: sl::SecurityLevel,
+{
+    /// Moves the `Result` into the `Sec`.
+    pub fn from_result(result: Result<Sec<S, A>, Sec<S, E>>) -> Self {
+        Sec::new(result.map(|sec| sec.data).map_err(|sec| sec.data))
+    }
+
+    /// Moves the `Result` out of the `Sec`, revealing whether it's an error, but keeping both value and error labeled.
+    /// Like `reveal`, it must be supplied with a `Clearance` for a security level &geq; the `Sec`'s.
+    pub fn transpose<S2>(self, _: &Clearance<S2>) -> Result<Sec<S, A>, Sec<S, E>>
+    where
+        S2: sl::SecurityLevel<S> + sl::SecurityLevel,
+    {
+        self.data.map(Sec::new).map_err(Sec::new)
+    }
+}
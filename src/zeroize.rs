@@ -0,0 +1,236 @@
+//! Zeroize-on-drop storage for secrets.
+//!
+//! Dropping a `Sec<High, String>` frees its buffer, but leaves the bytes behind in freed heap memory,
+//! where a later allocation, a core dump, or a memory disclosure bug may find them.
+//! A `Zeroizing` payload overwrites its data with zeros when it's dropped instead.
+//!
+//! The zeros are written with `ptr::write_volatile`, followed by a compiler fence,
+//! so the compiler can't optimize the writes away as dead stores to memory that's about to be freed.
+//! Only the current buffer is wiped: copies made by reallocating a growing `Vec` or `String` are not.
+//! Reserve enough capacity up front to avoid those.
+
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::sync::atomic::{self, Ordering};
+
+use super::Sec;
+use super::security_level as sl;
+
+// Overwrites `len` bytes at `ptr` with zeros. `ptr` must be valid for writes of `len` bytes
+unsafe fn volatile_zero(ptr: *mut u8, len: usize) {
+    for i in 0..len {
+        // SAFETY: `i < len`, and the caller vouches for `len` bytes at `ptr`.
+        ptr::write_volatile(ptr.add(i), 0);
+    }
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+/// Data that can overwrite itself with zeros.
+///
+/// Collections zero their elements, and then the whole of their buffer, including any spare capacity,
+/// leaving the collection empty.
+pub trait Zeroize {
+    /// Overwrites the data with zeros.
+    fn zeroize(&mut self);
+}
+
+macro_rules! zeroize_with {
+    ($zero:expr => $($t:ty),*) => {
+        $(
+            impl Zeroize for $t {
+                fn zeroize(&mut self) {
+                    // SAFETY: `self` is a valid, exclusive reference, and the type has no destructor to skip.
+                    unsafe { ptr::write_volatile(self, $zero) };
+                    atomic::compiler_fence(Ordering::SeqCst);
+                }
+            }
+        )*
+    };
+}
+
+zeroize_with!(0 => u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+zeroize_with!(false => bool);
+zeroize_with!('\0' => char);
+
+impl<T> Zeroize for Vec<T>
+where
+    T: Zeroize,
+{
+    fn zeroize(&mut self) {
+        for item in self.iter_mut() {
+            item.zeroize();
+        }
+        self.clear();
+        // SAFETY: the buffer holds `capacity` elements, and is all spare capacity now that the vector is empty,
+        // so plain bytes may be written over it.
+        unsafe { volatile_zero(self.as_mut_ptr() as *mut u8, self.capacity() * mem::size_of::<T>()) };
+    }
+}
+
+impl Zeroize for String {
+    fn zeroize(&mut self) {
+        // SAFETY: all zeros is valid UTF-8, and the string is left empty anyway.
+        unsafe { self.as_mut_vec() }.zeroize();
+    }
+}
+
+impl<T> Zeroize for Box<[T]>
+where
+    T: Zeroize,
+{
+    fn zeroize(&mut self) {
+        for item in self.iter_mut() {
+            item.zeroize();
+        }
+    }
+}
+
+impl<T, const N: usize> Zeroize for [T; N]
+where
+    T: Zeroize,
+{
+    fn zeroize(&mut self) {
+        for item in self.iter_mut() {
+            item.zeroize();
+        }
+    }
+}
+
+impl<T> Zeroize for Option<T>
+where
+    T: Zeroize,
+{
+    fn zeroize(&mut self) {
+        if let Some(ref mut data) = *self {
+            data.zeroize();
+        }
+        *self = None;
+    }
+}
+
+/// Data that's overwritten with zeros when dropped.
+///
+/// It derefs to the data, but can't be unwrapped, so the data isn't moved out of it by accident.
+/// Passed to `map` or `and_then`, it's wiped as soon as the function is done with it,
+/// unless the function hands it on.
+///
+/// `DerefMut` does still let the data be swapped out, with `mem::take` or `mem::replace`,
+/// and whatever is swapped out isn't wiped. Modify the data in place instead.
+///
+/// Formatting it never shows the data, like with a `Sec`.
+///
+/// # Example
+/// ```
+/// use seclib::prelude::*;
+/// use seclib::zeroize::Zeroizing;
+///
+/// let high = Authority::acquire().unwrap().clearance::<High>();
+///
+/// let password: Sec<High, Zeroizing<String>> = Sec::new_zeroizing("hunter2".to_string());
+/// let length = password.map(|p| p.len()); // the password is wiped here
+///
+/// assert_eq!(length.reveal(&high), 7);
+/// ```
+pub struct Zeroizing<A>(A)
+where
+    A: Zeroize;
+
+impl<A> Zeroizing<A>
+where
+    A: Zeroize,
+{
+    /// Constructor.
+    pub fn new(data: A) -> Self {
+        Zeroizing(data)
+    }
+}
+
+impl<A> Deref for Zeroizing<A>
+where
+    A: Zeroize,
+{
+    type Target = A;
+
+    fn deref(&self) -> &A {
+        &self.0
+    }
+}
+
+impl<A> DerefMut for Zeroizing<A>
+where
+    A: Zeroize,
+{
+    fn deref_mut(&mut self) -> &mut A {
+        &mut self.0
+    }
+}
+
+impl<A> Clone for Zeroizing<A>
+where
+    A: Zeroize + Clone,
+{
+    fn clone(&self) -> Self {
+        Zeroizing(self.0.clone())
+    }
+}
+
+impl<A> fmt::Debug for Zeroizing<A>
+where
+    A: Zeroize,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Zeroizing(<redacted>)")
+    }
+}
+
+impl<A> Drop for Zeroizing<A>
+where
+    A: Zeroize,
+{
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<S, A> Sec<S, Zeroizing<A>>
+where
+    S: sl::SecurityLevel,
+    A: Zeroize,
+{
+    /// Constructor. Wraps `data` so that it's overwritten with zeros when dropped.
+    pub fn new_zeroizing(data: A) -> Self {
+        Sec::new(Zeroizing(data))
+    }
+
+    /// Like `map`, but the result is zeroized on drop too, and the input is wiped right after `f` is done with it.
+    ///
+    /// # Example
+    /// ```
+    /// use seclib::prelude::*;
+    ///
+    /// let high = Authority::acquire().unwrap().clearance::<High>();
+    ///
+    /// let password = Sec::<High, _>::new_zeroizing("hunter2".to_string());
+    /// let shouted = password.map_zeroizing(|p| p.to_uppercase());
+    ///
+    /// assert_eq!(shouted.map(|s| s.as_str() == "HUNTER2").reveal(&high), true);
+    /// ```
+    pub fn map_zeroizing<B, F>(self, f: F) -> Sec<S, Zeroizing<B>>
+    where
+        B: Zeroize,
+        F: FnOnce(&A) -> B,
+    {
+        self.map(|data| Zeroizing(f(&data)))
+    }
+
+    /// Like `and_then`, but the result is zeroized on drop too, and the input is wiped right after `f` is done with it.
+    pub fn and_then_zeroizing<B, F>(self, f: F) -> Sec<S, Zeroizing<B>>
+    where
+        B: Zeroize,
+        F: FnOnce(&A) -> Sec<S, B>,
+    {
+        self.and_then(|data| f(&data).map(Zeroizing))
+    }
+}
@@ -0,0 +1,163 @@
+//! Checks that every forbidden flow is rejected by the compiler, with the expected diagnostics.
+//!
+//! Every program in `tests/ui` is compiled against the library with `rustc`.
+//! Lines that must not compile carry a `//~ ERROR <message>` comment, where `<message>` is part of the diagnostic,
+//! e.g. `//~ ERROR E0308: mismatched types`, or just the message for errors without a code. A program passes when each annotated line fails with a matching error,
+//! and nothing else fails, so a refactor can neither let a flow through nor break a program for some other reason.
+//!
+//! Programs for modules that aren't built on the target, like `locked`, are skipped.
+//!
+//! Module paths are stripped from the diagnostics, so `seclib::security_level::High` can be written as just `High`.
+
+extern crate seclib;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const ANNOTATION: &str = "//~ ERROR ";
+
+// An error reported by rustc, as (line, "E0308: mismatched types: ...")
+type Diagnostic = (usize, String);
+
+// The rlib of the library, next to the test binary
+fn library() -> PathBuf {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+
+    fs::read_dir(&deps).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            name.starts_with("libseclib-") && name.ends_with(".rlib")
+        })
+        .max_by_key(|path| fs::metadata(path).and_then(|m| m.modified()).unwrap())
+        .expect("the seclib rlib should have been built alongside the tests")
+}
+
+// Removes module paths like `seclib::security_level::`, keeping only the last segment
+fn strip_paths(message: &str) -> String {
+    let mut result = String::with_capacity(message.len());
+    let mut rest = message;
+
+    while let Some(start) = rest.find("seclib::") {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        // skip `ident::` segments, as long as another `::` follows
+        while let Some(end) = rest.find("::") {
+            if rest[..end].chars().all(|c| c.is_alphanumeric() || c == '_') {
+                rest = &rest[end + 2..];
+            } else {
+                break;
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn expected(source: &str) -> Vec<Diagnostic> {
+    source.lines()
+        .enumerate()
+        .filter_map(|(i, line)| line.find(ANNOTATION).map(|at| (i + 1, line[at + ANNOTATION.len()..].trim().to_string())))
+        .collect()
+}
+
+// Parses the `--error-format=short` output, e.g. `tests/ui/sec.rs:7:17: error[E0277]: the trait bound ...`
+fn actual(file: &str, stderr: &str) -> Vec<Diagnostic> {
+    stderr.lines()
+        .filter_map(|line| {
+            let rest = line.strip_prefix(file)?.strip_prefix(':')?;
+            let mut parts = rest.splitn(3, ':');
+            let line_number = parts.next()?.parse().ok()?;
+            let message = parts.nth(1)?.trim().strip_prefix("error")?;
+            if let Some(message) = message.strip_prefix('[') {
+                let close = message.find(']')?;
+                Some((line_number, strip_paths(&format!("{}{}", &message[..close], &message[close + 1..]))))
+            } else {
+                // errors without a code, e.g. from a macro
+                Some((line_number, strip_paths(message.strip_prefix(": ")?)))
+            }
+        })
+        .collect()
+}
+
+// Whether the program's module is built for this target, e.g. `locked` is only built on some
+fn supported(file: &Path) -> bool {
+    match file.file_stem().and_then(|stem| stem.to_str()) {
+        Some("locked") => cfg!(all(
+            target_os = "linux",
+            any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64")
+        )),
+        _ => true,
+    }
+}
+
+fn check(file: &Path, library: &Path, out_dir: &Path) -> Result<(), String> {
+    let name = file.to_string_lossy().into_owned();
+    let source = fs::read_to_string(file).unwrap();
+    let expected = expected(&source);
+
+    let output = Command::new(env::var("RUSTC").unwrap_or_else(|_| "rustc".into()))
+        .args(["--crate-type", "bin", "--emit=metadata", "--error-format=short", "-A", "warnings"])
+        .arg("--extern").arg(format!("seclib={}", library.display()))
+        .arg("-L").arg(format!("dependency={}", library.parent().unwrap().display()))
+        .arg("--out-dir").arg(out_dir)
+        .arg(&name)
+        .output()
+        .unwrap();
+
+    if output.status.success() {
+        return Err(format!("{} compiled, but shouldn't have", name));
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let actual = actual(&name, &stderr);
+    let mut problems = Vec::new();
+
+    for &(line, ref message) in &expected {
+        if !actual.iter().any(|&(l, ref m)| l == line && m.contains(message.as_str())) {
+            problems.push(format!("{}:{}: expected error `{}`", name, line, message));
+        }
+    }
+    for &(line, ref message) in &actual {
+        if !expected.iter().any(|&(l, _)| l == line) {
+            problems.push(format!("{}:{}: unexpected error `{}`", name, line, message));
+        }
+    }
+    if expected.is_empty() {
+        problems.push(format!("{}: no `{}` annotations", name, ANNOTATION.trim()));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{}\n--- rustc output ---\n{}", problems.join("\n"), stderr))
+    }
+}
+
+#[test]
+fn compile_fail() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    env::set_current_dir(root).unwrap();
+
+    let library = library();
+    let out_dir = env::temp_dir().join(format!("seclib-ui-{}", std::process::id()));
+    fs::create_dir_all(&out_dir).unwrap();
+
+    let mut files: Vec<PathBuf> = fs::read_dir("tests/ui").unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs") && supported(path))
+        .collect();
+    files.sort();
+
+    let failures: Vec<String> = files.iter()
+        .filter_map(|file| check(file, &library, &out_dir).err())
+        .collect();
+
+    fs::remove_dir_all(&out_dir).unwrap();
+
+    assert!(!files.is_empty(), "no programs in tests/ui");
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
@@ -0,0 +1,98 @@
+//! Statistical timing tests for the constant-time operations, in the style of dudect.
+//!
+//! Each operation is timed many times on two classes of inputs: one where the guess equals the secret,
+//! and one where it's random. Welch's t-test then says whether the two classes of timings differ.
+//! dudect considers a |t| above 10 a definite leak; `labeled_eq` serves as a control that the test can see one.
+//!
+//! Timings depend on the machine and whatever else it's running, so the tests are ignored by default,
+//! and only run when asked for, with `cargo test --test constant_time -- --ignored`, ideally on an idle machine.
+//! The results of the operations are checked in the unit tests.
+//!
+//! See "Dude, is my code constant time?" by Reparaz, Balasch and Verbauwhede.
+
+extern crate seclib;
+
+use std::hint;
+use std::time::Instant;
+
+use seclib::prelude::*;
+use seclib::testing::Rng;
+
+const LEN: usize = 512;
+const MEASUREMENTS: usize = 4000;
+const CALLS_PER_MEASUREMENT: usize = 4;
+const THRESHOLD: f64 = 10.0;
+
+fn bytes(rng: &mut Rng) -> Vec<u8> {
+    (0..LEN).map(|_| rng.next_u64() as u8).collect()
+}
+
+// Welch's t statistic for the difference in means of two samples
+fn welch_t(a: &[f64], b: &[f64]) -> f64 {
+    let mean = |xs: &[f64]| xs.iter().sum::<f64>() / xs.len() as f64;
+    let variance = |xs: &[f64], m: f64| xs.iter().map(|x| (x - m) * (x - m)).sum::<f64>() / (xs.len() - 1) as f64;
+
+    let (ma, mb) = (mean(a), mean(b));
+    let (va, vb) = (variance(a, ma), variance(b, mb));
+    (ma - mb) / (va / a.len() as f64 + vb / b.len() as f64).sqrt()
+}
+
+// Times `f` on the secret against guesses of either class, in random order, and returns the t statistic
+fn leakage<R, F>(f: F) -> f64
+where
+    F: Fn(&Sec<High, Vec<u8>>, &Sec<High, Vec<u8>>) -> R,
+{
+    let mut rng = Rng::new(0x5EC11B);
+    let secret_bytes = bytes(&mut rng);
+    let secret: Sec<High, Vec<u8>> = Sec::new(secret_bytes.clone());
+
+    let classes: Vec<bool> = (0..MEASUREMENTS).map(|_| rng.coin()).collect();
+    let guesses: Vec<Sec<High, Vec<u8>>> = classes.iter()
+        .map(|&fixed| Sec::new(if fixed { secret_bytes.clone() } else { bytes(&mut rng) }))
+        .collect();
+
+    let timings: Vec<f64> = guesses.iter()
+        .map(|guess| {
+            let start = Instant::now();
+            for _ in 0..CALLS_PER_MEASUREMENT {
+                hint::black_box(f(hint::black_box(&secret), hint::black_box(guess)));
+            }
+            start.elapsed().as_nanos() as f64
+        })
+        .collect();
+
+    // crop the slowest measurements, which are mostly interrupts and the like
+    let mut sorted = timings.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let cutoff = sorted[sorted.len() * 9 / 10];
+
+    let (mut fixed, mut random) = (Vec::new(), Vec::new());
+    for (&timing, &class) in timings.iter().zip(&classes) {
+        if timing <= cutoff {
+            if class { fixed.push(timing) } else { random.push(timing) }
+        }
+    }
+    welch_t(&fixed, &random)
+}
+
+#[test]
+#[ignore = "timing-dependent, run with --ignored"]
+fn test_labeled_eq_leaks() {
+    // the control: `labeled_eq` keeps the answer secret, but compares with `==`, which stops at the first difference
+    let t = leakage(|secret, guess| secret.labeled_eq(guess));
+    assert!(t.abs() > THRESHOLD, "t = {}", t);
+}
+
+#[test]
+#[ignore = "timing-dependent, run with --ignored"]
+fn test_ct_eq_doesnt_leak() {
+    let t = leakage(|secret, guess| secret.ct_eq(guess));
+    assert!(t.abs() < THRESHOLD, "t = {}", t);
+}
+
+#[test]
+#[ignore = "timing-dependent, run with --ignored"]
+fn test_ct_lt_doesnt_leak() {
+    let t = leakage(|secret, guess| secret.ct_lt(guess));
+    assert!(t.abs() < THRESHOLD, "t = {}", t);
+}
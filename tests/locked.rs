@@ -0,0 +1,108 @@
+//! Checks the page protections of locked memory, as reported by the kernel in `/proc/self/smaps`.
+#![cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64", target_arch = "arm", target_arch = "aarch64")))]
+
+extern crate seclib;
+
+use std::fs;
+use std::panic;
+
+use seclib::prelude::*;
+use seclib::locked::Locked;
+
+// The mapping containing `address`, as its permissions, e.g. "r--p", and its locked size in kB
+fn mapping(address: usize) -> (String, usize) {
+    let smaps = fs::read_to_string("/proc/self/smaps").unwrap();
+    let mut lines = smaps.lines();
+
+    while let Some(line) = lines.next() {
+        let mut fields = line.split_whitespace();
+        let range = fields.next().unwrap();
+        let (start, end) = match range.find('-') {
+            Some(dash) if !range.ends_with(':') => (&range[..dash], &range[dash + 1..]),
+            _ => continue,
+        };
+        let start = usize::from_str_radix(start, 16).unwrap();
+        let end = usize::from_str_radix(end, 16).unwrap();
+        if !(start..end).contains(&address) {
+            continue;
+        }
+
+        let permissions = fields.next().unwrap().to_string();
+        let locked = lines.by_ref()
+            .find(|line| line.starts_with("Locked:"))
+            .map(|line| line.split_whitespace().nth(1).unwrap().parse().unwrap())
+            .unwrap();
+        return (permissions, locked);
+    }
+    panic!("no mapping contains {:#x}", address)
+}
+
+fn page_size() -> usize {
+    // the smaps entry of any mapping says
+    let smaps = fs::read_to_string("/proc/self/smaps").unwrap();
+    let line = smaps.lines().find(|line| line.starts_with("KernelPageSize:")).unwrap();
+    line.split_whitespace().nth(1).unwrap().parse::<usize>().unwrap() * 1024
+}
+
+#[test]
+fn test_pages_are_locked_and_protected() {
+    let mut key = Locked::new([0x42u8; 32]).unwrap();
+    let page = page_size();
+
+    let address = key.with(|key| {
+        let address = key.as_ptr() as usize;
+        assert_eq!(mapping(address).0, "r--p");
+        address
+    });
+    key.with_mut(|key| {
+        assert_eq!(mapping(address).0, "rw-p");
+        key[0] = 0;
+    });
+
+    // inaccessible while not in use, but still locked into memory
+    let (permissions, locked) = mapping(address);
+    assert_eq!(permissions, "---p");
+    assert!(locked * 1024 >= page, "{} kB locked", locked);
+
+    // the value sits right at the end of its page, followed by a guard page
+    assert_eq!((address + 32) % page, 0);
+    key.with(|_| {
+        assert_eq!(mapping(address + 32).0, "---p");
+        assert_eq!(mapping(address - page).0, "---p");
+        assert_eq!(mapping(address + 32).1, 0);
+    });
+
+    assert_eq!(format!("{:?}", key), "Locked(32 bytes)");
+}
+
+#[test]
+fn test_nested_and_panicking_access() {
+    let key = Locked::new(7u64).unwrap();
+    let address = key.with(|key| key as *const u64 as usize);
+
+    // the inner access ending doesn't lock the outer one out
+    let sum = key.with(|a| key.with(|b| a + b) + *a);
+    assert_eq!(sum, 21);
+    assert_eq!(mapping(address).0, "---p");
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| key.with(|_| panic!("interrupted"))));
+    assert!(result.is_err());
+    assert_eq!(mapping(address).0, "---p");
+}
+
+// The only test acquiring the authority, as that can only be done once per process
+#[test]
+fn test_sec_with_locked_storage() {
+    let high = Authority::acquire().unwrap().clearance::<High>();
+
+    let mut key: Sec<High, Locked<[u8; 16]>> = Sec::new_locked([1; 16]).unwrap();
+    key.with_mut(|key| key[15] = 2);
+
+    let sum = key.with(|key| key.iter().map(|&b| b as u32).sum::<u32>());
+    assert_eq!(sum.reveal(&high), 17);
+    assert_eq!(format!("{:?}", key), "Sec<High>(<redacted>)");
+
+    // values bigger than a page span several
+    let big: Sec<High, Locked<[u64; 1024]>> = Sec::new_locked([3; 1024]).unwrap();
+    assert_eq!(big.with(|big| big.iter().sum::<u64>()).reveal(&high), 3072);
+}
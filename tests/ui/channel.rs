@@ -0,0 +1,18 @@
+// Channels only carry data up, and hand it out at their own level.
+extern crate seclib;
+
+use seclib::prelude::*;
+use seclib::channel::{sec_channel, sync_sec_channel, SecSelect};
+
+fn main() {
+    let (public, public_receiver) = sec_channel::<Low, i32>();
+    let _ = public.send(Sec::<High, i32>::new(1)); //~ ERROR E0308: mismatched types: expected `Sec<Low, i32>`, found `Sec<High, i32>`
+
+    let (secret, secret_receiver) = sync_sec_channel::<High, i32>(1);
+    let _ = secret.send(Sec::<Low, i32>::new(1));
+    let _: Sec<Low, i32> = secret_receiver.recv().unwrap(); //~ ERROR E0308: mismatched types: expected `Sec<Low, i32>`, found `Sec<High, i32>`
+
+    let mut select = SecSelect::<Low, i32>::new();
+    select.add(&public_receiver);
+    select.add(&secret_receiver); //~ ERROR E0308: mismatched types: expected `&SecReceiver<Low, i32>`, found `&SecReceiver<High, i32>`
+}
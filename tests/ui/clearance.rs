@@ -0,0 +1,20 @@
+// Clearances only come from the `Authority`, and the ordering of levels can't be extended from outside.
+extern crate seclib;
+
+use seclib::prelude::*;
+use seclib::security_level::SecurityLevel;
+use seclib::integrity::IntegrityLevel;
+
+#[derive(Debug)]
+struct Mine;
+
+impl SecurityLevel<High> for Mine {} //~ ERROR E0277: the trait bound `Mine: security_level::sealed::Sealed<High>` is not satisfied
+impl IntegrityLevel<Mine> for Trusted {} //~ ERROR E0277: the trait bound `Trusted: integrity::sealed::Sealed<Mine>` is not satisfied
+
+fn main() {
+    let authority = Authority::acquire().unwrap();
+
+    let _ = Sec::<High, i32>::new(1).reveal(High); //~ ERROR E0308: mismatched types: expected `&Clearance<_>`, found `High`
+    let _ = Sec::<High, i32>::new(1).reveal(&Clearance::<High>::mint()); //~ ERROR E0624: associated function `mint` is private
+    let _ = authority.clearance::<Low>().lower::<High>(); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+}
@@ -0,0 +1,19 @@
+// Constant-time comparisons and selections only combine data at one level, and keep their answers there.
+extern crate seclib;
+
+use seclib::prelude::*;
+use seclib::constant_time::Choice;
+
+fn main() {
+    let secret: Sec<High, u64> = Sec::new(1);
+    let public: Sec<Low, u64> = Sec::new(2);
+
+    let _ = secret.ct_eq(&public); //~ ERROR E0308: mismatched types: expected `&Sec<High, u64>`, found `&Sec<Low, u64>`
+    let _ = secret.ct_lt(&public); //~ ERROR E0308: mismatched types: expected `&Sec<High, u64>`, found `&Sec<Low, u64>`
+    let _: Sec<Low, Choice> = secret.ct_eq(&secret); //~ ERROR E0308: mismatched types: expected `Sec<Low, Choice>`, found `Sec<High, Choice>`
+
+    // a secret choice can't pick between public values
+    let choice: Sec<High, Choice> = secret.ct_lt(&secret);
+    let _ = public.ct_select(&public, &choice); //~ ERROR E0308: mismatched types: expected `&Sec<Low, Choice>`, found `&Sec<High, Choice>`
+    let _ = secret.ct_select(&public, &choice); //~ ERROR E0308: mismatched types: expected `&Sec<High, u64>`, found `&Sec<Low, u64>`
+}
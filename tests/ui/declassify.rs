@@ -0,0 +1,19 @@
+// Declassification only goes through a policy for exactly the levels involved, and only with clearance for the data.
+extern crate seclib;
+
+use seclib::prelude::*;
+use seclib::declassify::Policy;
+
+fn main() {
+    let authority = Authority::acquire().unwrap();
+    let high = authority.clearance::<High>();
+
+    let policy: Policy<Low, Low, i32, i32> = Policy::new("publish", |i| i);
+    let _: Sec<Low, i32> = Sec::<High, i32>::new(1).declassify(&policy, &high); //~ ERROR E0277: the trait bound `Policy<Low, Low, i32, i32>: Declassifier<High, _>` is not satisfied
+
+    let policy: Policy<High, Low, i32, i32> = Policy::new("publish", |i| i);
+    let _: Sec<Low, String> = Sec::<High, String>::new("secret".into()).declassify(&policy, &high); //~ ERROR E0271: type mismatch resolving `<Policy<High, Low, i32, i32> as Declassifier<High, Low>>::Input == String`
+
+    let policy: Policy<High, Low, i32, i32> = Policy::new("publish", |i| i);
+    let _: Sec<Low, i32> = Sec::<High, i32>::new(1).declassify(&policy, &authority.clearance::<Low>()); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+}
@@ -0,0 +1,15 @@
+// Acting as a principal takes their privilege, not just their name.
+extern crate seclib;
+
+use seclib::dlm::{DlmLabel, DlmSec};
+use seclib::principal::{Principal, PrincipalHierarchy};
+
+fn main() {
+    let alice = Principal::new("alice");
+    let mut hierarchy = PrincipalHierarchy::new();
+    let data: DlmSec<&str> = DlmSec::new(DlmLabel::public().with_policy(&alice, vec![]), "alice's diary");
+
+    let _ = data.clone().reveal_to(&alice, &hierarchy); //~ ERROR E0308: mismatched types: expected `&Privilege`, found `&Principal`
+    let _ = data.declassify(&alice, &hierarchy, &alice, vec![]); //~ ERROR E0308: mismatched types: expected `&Privilege`, found `&Principal`
+    hierarchy.add_acts_for(&Principal::new("mallory"), &alice); //~ ERROR E0308: mismatched types: expected `&Privilege`, found `&Principal`
+}
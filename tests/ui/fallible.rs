@@ -0,0 +1,15 @@
+// Whether a fallible computation failed stays at its level, along with the error.
+extern crate seclib;
+
+use seclib::prelude::*;
+use seclib::fallible::SecError;
+
+fn main() {
+    let low = Authority::acquire().unwrap().clearance::<Low>();
+    let pin: Sec<High, Result<u32, String>> = Sec::new(Ok(1234));
+
+    let _: Sec<Low, Result<u32, String>> = pin.clone().try_map(|p| Ok(p * 2)); //~ ERROR E0308: mismatched types: expected `Sec<Low, Result<u32, String>>`, found `Sec<High, Result<u32, String>>`
+    let _ = pin.clone().try_and_then(|p| Sec::<Low, Result<u32, String>>::new(Ok(p))); //~ ERROR E0308: mismatched types: expected `Sec<High, Result<_, String>>`, found `Sec<Low, Result<u32, String>>`
+    let _ = pin.clone().into_result(&low); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+    let _: String = SecError::<High, String>::new("secret".to_string()).reveal(&low); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+}
@@ -0,0 +1,17 @@
+// Secrets can't be written to public files, and a secret file's contents, and whether it could be read, stay secret.
+extern crate seclib;
+
+use seclib::prelude::*;
+use seclib::file::FileRegistry;
+
+fn main() {
+    let mut registry = FileRegistry::new();
+    registry.register::<Low>("public").register::<High>("secret");
+
+    let public = registry.open::<Low>("public/index.html").unwrap();
+    let _ = public.write_file(Sec::<High, &str>::new("hunter2")); //~ ERROR E0308: mismatched types: expected `Sec<Low, _>`, found `Sec<High, &str>`
+
+    let secret = registry.open::<High>("secret/key").unwrap();
+    let _: SecIO<Low, Sec<Low, std::io::Result<Vec<u8>>>> = secret.read_file(); //~ ERROR E0308: mismatched types
+    let _: SecIO<Low, std::io::Result<Sec<High, Vec<u8>>>> = secret.read_file(); //~ ERROR E0308: mismatched types
+}
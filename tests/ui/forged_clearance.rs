@@ -0,0 +1,13 @@
+// Neither clearances, privileges nor the authority can be built by hand.
+extern crate seclib;
+
+use std::marker::PhantomData;
+
+use seclib::prelude::*;
+use seclib::principal::{Principal, Privilege};
+
+fn main() {
+    let _: Clearance<High> = Clearance { security_level: PhantomData }; //~ ERROR E0451: field `security_level` of struct `Clearance` is private
+    let _ = Authority { _private: () }; //~ ERROR E0451: field `_private` of struct `Authority` is private
+    let _ = Privilege { principal: Principal::new("alice") }; //~ ERROR E0451: field `principal` of struct `Privilege` is private
+}
@@ -0,0 +1,20 @@
+// Untrusted data only becomes trusted through `endorse`.
+extern crate seclib;
+
+use seclib::prelude::*;
+
+fn execute(_: Int<Trusted, String>) {}
+
+fn main() {
+    let authority = Authority::acquire().unwrap();
+
+    let input: Int<Untrusted, String> = Int::new("'; DROP TABLE users; --".into());
+
+    execute(input.clone()); //~ ERROR E0308: mismatched types: expected `Int<Trusted, String>`, found `Int<Untrusted, String>`
+    let _ = input.clone().weaken(Trusted); //~ ERROR E0277: the trait bound `Trusted: IntegrityLevel<Untrusted>` is not satisfied
+    let _: Int<Trusted, String> = Int::new("rm -rf ~".into()); //~ ERROR E0308: mismatched types: expected `Int<Trusted, String>`, found `Int<Untrusted, _>`
+    let _: Int<Trusted, String> = input.map(|s| s); //~ ERROR E0308: mismatched types: expected `Int<Trusted, String>`, found `Int<Untrusted, String>`
+
+    let _ = Sec::<Pair<Low, Untrusted>, i32>::new(1).lift(Pair(High, Trusted)); //~ ERROR E0277: the trait bound `Pair<High, Trusted>: SecurityLevel<Pair<Low, Untrusted>>` is not satisfied
+    let _ = Sec::<Pair<High, Trusted>, i32>::new(1).reveal(&authority.clearance::<Pair<Low, Untrusted>>()); //~ ERROR E0277: the trait bound `Pair<Low, Untrusted>: SecurityLevel<Pair<High, Trusted>>` is not satisfied
+}
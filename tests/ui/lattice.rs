@@ -0,0 +1,25 @@
+// Levels declared with `security_lattice!` are just as strict as `Low` and `High`.
+#[macro_use]
+extern crate seclib;
+
+use seclib::prelude::*;
+
+security_lattice! {
+    pub Public < Internal < Secret
+}
+
+security_lattice! {
+    pub Staff;
+    pub Hr > Staff;
+    pub Finance > Staff;
+}
+
+fn main() {
+    let authority = Authority::acquire().unwrap();
+
+    Sec::<Secret, i32>::new(1).lift(Internal); //~ ERROR E0271: type mismatch resolving
+    Sec::<Internal, i32>::new(1).reveal(&authority.clearance::<Public>()); //~ ERROR E0271: type mismatch resolving
+    Sec::<Hr, i32>::new(1).lift(Finance); //~ ERROR E0271: type mismatch resolving
+    Sec::<Public, i32>::new(1).lift(High); //~ ERROR E0277: the trait bound `High: SecurityLevel<Public>` is not satisfied
+    let _: Sec<Staff, i32> = Sec::<Hr, i32>::new(1).zip_with(Sec::<Finance, i32>::new(2), |a, b| a + b); //~ ERROR E0308: mismatched types: expected `Sec<Staff, i32>`, found `Sec<NoBound<Full>, i32>`
+}
@@ -0,0 +1,27 @@
+// An LIO context's clearance comes from the authority, not from whoever creates the context.
+// Nor can a context be copied, to keep a current label that hasn't been raised yet.
+extern crate seclib;
+
+use seclib::prelude::*;
+use seclib::dynamic::Level;
+use seclib::lio::{Lio, LioSink};
+
+fn main() {
+    let _ = Lio::new(Level::Low, Level::High); //~ ERROR E0308: mismatched types: expected `&DynClearance<Level>`, found `Level`
+
+    let high = Authority::acquire().unwrap().dyn_clearance(Level::High);
+    let secret: Sec<High, &str> = Sec::new("hunter2");
+    let mut public = LioSink::new(Level::Low, Vec::new());
+
+    let mut lio = Lio::new(Level::Low, &high).unwrap();
+    let _ = lio.to_labeled(Level::High, |inner| {
+        let mut fresh = inner.clone(); //~ ERROR E0599: no method named `clone` found for mutable reference `&mut Lio<Level>`
+        let password = inner.unlabel_sec(secret)?;
+        fresh.write(&mut public, password.as_bytes())
+    });
+    let _ = Lio::new(Level::Low, &high).unwrap().run(|lio| {
+        let mut fresh = lio.clone(); //~ ERROR E0599: no method named `clone` found for mutable reference `&mut Lio<Level>`
+        let password = lio.unlabel_sec(Sec::<High, &str>::new("hunter2"))?;
+        fresh.write(&mut public, password.as_bytes())
+    });
+}
@@ -0,0 +1,14 @@
+// Locked memory is only lent out at the level it's kept at.
+extern crate seclib;
+
+use seclib::prelude::*;
+use seclib::locked::Locked;
+
+fn main() {
+    let low = Authority::acquire().unwrap().clearance::<Low>();
+    let mut key: Sec<High, Locked<[u8; 32]>> = Sec::new_locked([7; 32]).unwrap();
+
+    let _: Sec<Low, u8> = key.with(|k| k[0]); //~ ERROR E0308: mismatched types: expected `Sec<Low, u8>`, found `Sec<High, u8>`
+    let _: Sec<Low, ()> = key.with_mut(|k| k[0] = 0); //~ ERROR E0308: mismatched types: expected `Sec<Low, ()>`, found `Sec<High, ()>`
+    let _ = key.with(|k| k[0]).reveal(&low); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+}
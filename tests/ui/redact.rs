@@ -0,0 +1,17 @@
+// Formatting and comparing secrets needs clearance too.
+extern crate seclib;
+
+use seclib::prelude::*;
+
+fn secret() -> Sec<High, i32> {
+    Sec::new(42)
+}
+
+fn main() {
+    let authority = Authority::acquire().unwrap();
+
+    println!("{}", secret().unredacted(&authority.clearance::<Low>())); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+    let _ = secret().cleared(&authority.clearance::<Low>()); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+    let _ = Sec::<Low, i32>::new(1).labeled_eq(&secret()); //~ ERROR E0308: mismatched types: expected `&Sec<Low, i32>`, found `&Sec<High, i32>`
+    let _: Sec<Low, bool> = secret().labeled_eq(&secret()); //~ ERROR E0308: mismatched types: expected `Sec<Low, bool>`, found `Sec<High, bool>`
+}
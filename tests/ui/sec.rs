@@ -0,0 +1,26 @@
+// High data can't end up at Low through any of the core `Sec` combinators.
+extern crate seclib;
+
+use seclib::prelude::*;
+
+fn secret() -> Sec<High, i32> {
+    Sec::new(42)
+}
+
+fn main() {
+    let authority = Authority::acquire().unwrap();
+
+    secret().lift(Low); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+    secret().reveal(&authority.clearance::<Low>()); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+
+    let _: Sec<Low, i32> = secret().map(|i| i + 1); //~ ERROR E0308: mismatched types: expected `Sec<Low, i32>`, found `Sec<High, i32>`
+    let _ = Sec::<Low, i32>::new(1).and_then(|_| secret()); //~ ERROR E0308: mismatched types: expected `Sec<Low, _>`, found `Sec<High, i32>`
+    let _: Sec<Low, i32> = secret().and_then(Sec::new); //~ ERROR E0308: mismatched types: expected `Sec<Low, i32>`, found `Sec<High, i32>`
+    let _: Sec<Low, i32> = secret().zip_with(Sec::<Low, i32>::new(1), |a, b| a + b); //~ ERROR E0308: mismatched types: expected `Sec<Low, i32>`, found `Sec<High, i32>`
+
+    let _: Sec<Low, i32> = secret().into(); //~ ERROR E0277: the trait bound `Sec<Low, i32>: From<Sec<High, i32>>` is not satisfied
+    let _: Sec<Low, i32> = From::from(secret()); //~ ERROR E0277: the trait bound `Sec<Low, i32>: From<Sec<High, i32>>` is not satisfied
+
+    let _ = secret().data; //~ ERROR E0616: field `data` of struct `Sec` is private
+    let _ = secret() == secret(); //~ ERROR E0369: binary operation `==` cannot be applied to type `Sec<High, i32>`
+}
@@ -0,0 +1,17 @@
+// Secrets can't be written to public sinks, or pulled into public computations.
+extern crate seclib;
+
+use seclib::prelude::*;
+
+fn main() {
+    let _ = SecIO::<Low, i32>::value(Sec::<High, i32>::new(1)); //~ ERROR E0308: mismatched types: expected `Sec<Low, i32>`, found `Sec<High, i32>`
+    let _ = SecIO::<Low, i32>::new(1).plug(High); //~ ERROR E0308: mismatched types: expected `Low`, found `High`
+
+    let public: Sink<Low, Vec<u8>> = Sink::new(Vec::new());
+    let _ = SecIO::<High, _>::write(&public, "secret"); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+
+    let secret: Sink<High, Vec<u8>> = Sink::new(Vec::new());
+    let _ = secret.into_inner(&Authority::acquire().unwrap().clearance::<Low>()); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+
+    let _: Sec<Low, i32> = SecIO::<High, i32>::new(1).run(); //~ ERROR E0308: mismatched types: expected `Sec<Low, i32>`, found `Sec<High, i32>`
+}
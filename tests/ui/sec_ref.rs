@@ -0,0 +1,20 @@
+// Labeled references only take writes from below, and only give reads at their own level.
+extern crate seclib;
+
+use seclib::prelude::*;
+use seclib::sec_ref::{SecRef, SyncSecRef};
+
+fn main() {
+    let public: SecRef<Low, i32> = SecRef::new(0);
+    public.write(Sec::<High, i32>::new(1)); //~ ERROR E0308: mismatched types: expected `Sec<Low, i32>`, found `Sec<High, i32>`
+    public.update(Sec::<High, i32>::new(1), |a, b| *a += b); //~ ERROR E0308: mismatched types: expected `Sec<Low, _>`, found `Sec<High, i32>`
+    let _: SecRef<Low, i32> = SecRef::from_sec(Sec::<High, i32>::new(1)); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+
+    let secret: SecRef<High, i32> = SecRef::new(0);
+    let _: Sec<Low, i32> = secret.read(); //~ ERROR E0308: mismatched types: expected `Sec<Low, i32>`, found `Sec<High, i32>`
+
+    let public: SyncSecRef<Low, i32> = SyncSecRef::new(0);
+    public.write(Sec::<High, i32>::new(1)); //~ ERROR E0308: mismatched types: expected `Sec<Low, i32>`, found `Sec<High, i32>`
+    let secret: SyncSecRef<High, i32> = SyncSecRef::new(0);
+    let _: Sec<Low, i32> = secret.read_with(|i| *i); //~ ERROR E0308: mismatched types: expected `Sec<Low, i32>`, found `Sec<High, i32>`
+}
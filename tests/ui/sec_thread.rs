@@ -0,0 +1,11 @@
+// A labeled thread's result, and whether it panicked, stay at the thread's level.
+extern crate seclib;
+
+use seclib::prelude::*;
+use seclib::sec_thread::{self, SecPanic};
+
+fn main() {
+    let _: Sec<Low, Result<i32, SecPanic>> = sec_thread::spawn(|| Sec::<High, i32>::new(1)).join(); //~ ERROR E0308: mismatched types: expected `Sec<Low, Result<i32, SecPanic>>`, found `Sec<High, Result<i32, SecPanic>>`
+    let _: Result<i32, SecPanic> = Sec::<High, i32>::new(1).spawn_map(|i| i + 1).join(); //~ ERROR E0308: mismatched types: expected `Result<i32, SecPanic>`, found `Sec<High, Result<i32, SecPanic>>`
+    let _ = Sec::<High, i32>::new(1).spawn_and_then(|i| Sec::<Low, i32>::new(i)).join().reveal(&Authority::acquire().unwrap().clearance::<Low>()); //~ ERROR E0308: mismatched types: expected `Sec<High, _>`, found `Sec<Low, i32>`
+}
@@ -0,0 +1,14 @@
+// Padding hides how long a computation took, but not what it computed.
+extern crate seclib;
+
+use std::time::Duration;
+use seclib::prelude::*;
+
+fn main() {
+    let low = Authority::acquire().unwrap().clearance::<Low>();
+    let password: Sec<High, &str> = Sec::new("hunter2");
+
+    let _: Sec<Low, bool> = password.clone().map_padded(Duration::from_millis(1), |p| p == "hunter2"); //~ ERROR E0308: mismatched types: expected `Sec<Low, bool>`, found `Sec<High, bool>`
+    let _ = password.clone().map_padded(Duration::from_millis(1), |p| p.len()).reveal(&low); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+    let _ = password.map_padded(1000, |p| p.len()); //~ ERROR E0277: the trait bound `{integer}: PaddingPolicy` is not satisfied
+}
@@ -0,0 +1,14 @@
+// Taking an `Option` or `Result` out of a `Sec` reveals which variant it is.
+extern crate seclib;
+
+use seclib::prelude::*;
+
+fn main() {
+    let authority = Authority::acquire().unwrap();
+
+    let _ = Sec::<High, Option<i32>>::new(Some(1)).transpose(&authority.clearance::<Low>()); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+    let _ = Sec::<High, Result<i32, ()>>::new(Ok(1)).transpose(&authority.clearance::<Low>()); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+
+    let _: Sec<Low, Vec<i32>> = Sec::sequence(vec![Sec::<High, i32>::new(1)]); //~ ERROR E0308: mismatched types: expected `Sec<Low, Vec<i32>>`, found `Sec<High, Vec<i32>>`
+    let _: Sec<Low, Vec<i32>> = vec![Sec::<High, i32>::new(1)].into_iter().collect(); //~ ERROR E0277: a value of type `Sec<Low, Vec<i32>>` cannot be built from an iterator over elements of type `Sec<High, i32>`
+}
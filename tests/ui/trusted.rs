@@ -0,0 +1,10 @@
+// Only literals, which are part of the program itself, are trusted without being endorsed.
+#[macro_use]
+extern crate seclib;
+
+use seclib::prelude::*;
+
+fn main() {
+    let input: &'static str = String::from("'; DROP TABLE users; --").leak();
+    let _: Int<Trusted, &str> = trusted!(input); //~ ERROR no rules expected `input`
+}
@@ -0,0 +1,13 @@
+// Zeroizing combinators keep the level, and only take functions returning the same level.
+extern crate seclib;
+
+use seclib::prelude::*;
+
+fn main() {
+    let low = Authority::acquire().unwrap().clearance::<Low>();
+    let password = Sec::<High, _>::new_zeroizing("hunter2".to_string());
+
+    let _: Sec<Low, _> = password.clone().map_zeroizing(|p| p.len()); //~ ERROR E0308: mismatched types: expected `Sec<Low, _>`, found `Sec<High, Zeroizing<usize>>`
+    let _ = password.clone().and_then_zeroizing(|p| Sec::<Low, usize>::new(p.len())); //~ ERROR E0308: mismatched types: expected `Sec<High, _>`, found `Sec<Low, usize>`
+    let _ = password.map_zeroizing(|p| p.to_uppercase()).reveal(&low); //~ ERROR E0277: the trait bound `Low: SecurityLevel<High>` is not satisfied
+}
@@ -0,0 +1,127 @@
+//! Checks that zeroizing payloads really are wiped before their memory is freed.
+//!
+//! A global allocator snapshots the contents of one watched allocation as it's freed,
+//! which is the last moment anything can be said about it.
+
+extern crate seclib;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+use seclib::prelude::*;
+use seclib::zeroize::Zeroizing;
+
+const SNAPSHOT_SIZE: usize = 64;
+
+// The address of the allocation to snapshot, or 0
+static WATCHED: AtomicUsize = AtomicUsize::new(0);
+static SNAPSHOT_LEN: AtomicUsize = AtomicUsize::new(0);
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU8 = AtomicU8::new(0);
+static SNAPSHOT: [AtomicU8; SNAPSHOT_SIZE] = [ZERO; SNAPSHOT_SIZE];
+
+struct Snapshotting;
+
+// SAFETY: allocating and freeing are left to the system allocator, freed memory is only read before it's handed back.
+unsafe impl GlobalAlloc for Snapshotting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if WATCHED.compare_exchange(ptr as usize, 0, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+            let len = layout.size().min(SNAPSHOT_SIZE);
+            for (i, byte) in SNAPSHOT.iter().enumerate().take(len) {
+                // SAFETY: `i` is within the allocation, which is still live until passed on below.
+                byte.store(*ptr.add(i), Ordering::SeqCst);
+            }
+            SNAPSHOT_LEN.store(len, Ordering::SeqCst);
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Snapshotting = Snapshotting;
+
+// Only one allocation can be watched at a time
+static WATCHING: Mutex<()> = Mutex::new(());
+
+// Runs `f` while watching the allocation at `ptr`, returning its bytes as it was freed
+fn freed_bytes<F: FnOnce()>(ptr: *const u8, f: F) -> Vec<u8> {
+    let _guard = WATCHING.lock().unwrap_or_else(|e| e.into_inner());
+    SNAPSHOT_LEN.store(0, Ordering::SeqCst);
+    WATCHED.store(ptr as usize, Ordering::SeqCst);
+
+    f();
+
+    assert_eq!(WATCHED.swap(0, Ordering::SeqCst), 0, "the watched allocation wasn't freed");
+    SNAPSHOT.iter().take(SNAPSHOT_LEN.load(Ordering::SeqCst)).map(|byte| byte.load(Ordering::SeqCst)).collect()
+}
+
+#[test]
+fn test_plain_secrets_linger() {
+    // the control: without zeroizing, the secret is still there as it's freed
+    let password = String::from("correct horse battery staple");
+    let ptr = password.as_ptr();
+
+    let bytes = freed_bytes(ptr, || drop(Sec::<High, String>::new(password)));
+    assert!(bytes.starts_with(b"correct horse"));
+}
+
+#[test]
+fn test_zeroizing_secrets_are_wiped() {
+    let password = String::from("correct horse battery staple");
+    let ptr = password.as_ptr();
+
+    let bytes = freed_bytes(ptr, || drop(Sec::<High, _>::new_zeroizing(password)));
+    assert!(!bytes.is_empty());
+    assert!(bytes.iter().all(|&b| b == 0), "{:?}", bytes);
+}
+
+#[test]
+fn test_spare_capacity_is_wiped() {
+    let mut key: Vec<u8> = Vec::with_capacity(48);
+    key.extend_from_slice(b"0123456789abcdef0123456789abcdef");
+    key.truncate(8);
+    let ptr = key.as_ptr();
+
+    let bytes = freed_bytes(ptr, || drop(Zeroizing::new(key)));
+    assert_eq!(bytes.len(), 48);
+    assert!(bytes.iter().all(|&b| b == 0), "{:?}", bytes);
+}
+
+// The only test acquiring the authority, as that can only be done once per process
+#[test]
+fn test_map_wipes_what_it_consumes() {
+    let password = String::from("correct horse battery staple");
+    let ptr = password.as_ptr();
+    let secret = Sec::<High, _>::new_zeroizing(password);
+
+    let mut length = None;
+    let bytes = freed_bytes(ptr, || length = Some(secret.map(|p| p.len())));
+    assert!(bytes.iter().all(|&b| b == 0), "{:?}", bytes);
+
+    let password = String::from("correct horse battery staple");
+    let ptr = password.as_ptr();
+    let secret = Sec::<High, _>::new_zeroizing(password);
+
+    let mut words = None;
+    let bytes = freed_bytes(ptr, || words = Some(secret.map_zeroizing(|p| p.split(' ').count())));
+    assert!(bytes.iter().all(|&b| b == 0), "{:?}", bytes);
+
+    let password = String::from("correct horse battery staple");
+    let ptr = password.as_ptr();
+    let secret = Sec::<High, _>::new_zeroizing(password);
+
+    let mut upper = None;
+    let bytes = freed_bytes(ptr, || upper = Some(secret.and_then_zeroizing(|p| Sec::new(p.to_uppercase()))));
+    assert!(bytes.iter().all(|&b| b == 0), "{:?}", bytes);
+
+    // only the inputs were wiped, the results are intact
+    let high = Authority::acquire().unwrap().clearance::<High>();
+    assert_eq!(length.unwrap().reveal(&high), 28);
+    assert_eq!(*words.unwrap().reveal(&high), 4);
+    assert_eq!(upper.unwrap().reveal(&high).as_str(), "CORRECT HORSE BATTERY STAPLE");
+}